@@ -0,0 +1,127 @@
+use std::convert::TryFrom;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding { Down, Up, Nearest }
+
+impl Rounding {
+
+    pub fn from(name: &str) -> Option<Rounding> {
+        return match name {
+            "down" => Some(Rounding::Down),
+            "up" => Some(Rounding::Up),
+            "nearest" => Some(Rounding::Nearest),
+            _ => None,
+        };
+    }
+
+    fn name(&self) -> &'static str {
+        return match self {
+            Rounding::Down => "down",
+            Rounding::Up => "up",
+            Rounding::Nearest => "nearest",
+        };
+    }
+
+    fn divide(&self, n: i128, d: i128) -> i128 {
+        return match self {
+            Rounding::Down => n.div_euclid(d),
+            Rounding::Up => -(-n).div_euclid(d),
+            // halves round up
+            Rounding::Nearest => (2 * n + d).div_euclid(2 * d),
+        };
+    }
+}
+
+// `fuel = round(mass / divisor) - subtrahend`. Counting fuel for fuel stops
+// once a step needs `cutoff` or less.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Equation {
+    pub divisor: i64,
+    pub subtrahend: i64,
+    pub rounding: Rounding,
+    pub cutoff: i64,
+}
+
+// the equation from the puzzle
+pub const STANDARD: Equation = Equation { divisor: 3, subtrahend: 2, rounding: Rounding::Down, cutoff: 0 };
+
+impl Equation {
+
+    // Worked out in i128, where none of the steps can overflow; `None` if the
+    // result doesn't fit back into an i64.
+    pub fn fuel(&self, mass: &i64) -> Option<i64> {
+        let fuel = self.rounding.divide(*mass as i128, self.divisor as i128) - self.subtrahend as i128;
+        return i64::try_from(fuel).ok();
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.divisor < 1 {
+            return Err(format!("divisor must be at least 1, got {}", self.divisor));
+        }
+        if self.cutoff < 0 {
+            return Err(format!("cutoff must not be negative, got {}", self.cutoff));
+        }
+        return Ok(());
+    }
+
+    // Reads `key = value` lines for any of divisor, subtrahend, rounding
+    // (down, up or nearest) and cutoff, on top of `self`. Anything after a
+    // `#` is a comment.
+    pub fn configure(&self, text: &str) -> Result<Equation, Vec<String>> {
+
+        let mut equation = *self;
+        let mut errors = vec![];
+
+        for (n, line) in text.lines().enumerate() {
+
+            let line = line.split('#').next().unwrap().trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let (key, value) = match line.find('=') {
+                Some(i) => (line[..i].trim(), line[i + 1..].trim()),
+                None => {
+                    errors.push(format!("line {}: expected `key = value`, got `{}`", n + 1, line));
+                    continue;
+                },
+            };
+
+            if let Err(e) = equation.set(key, value) {
+                errors.push(format!("line {}: {}", n + 1, e));
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        return Ok(equation);
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+
+        let number = || value.parse::<i64>().map_err(|_| format!("{} must be a number, got `{}`", key, value));
+
+        match key {
+            "divisor" => self.divisor = number()?,
+            "subtrahend" => self.subtrahend = number()?,
+            "cutoff" => self.cutoff = number()?,
+            "rounding" => {
+                self.rounding = Rounding::from(value)
+                    .ok_or(format!("rounding must be down, up or nearest, got `{}`", value))?;
+            },
+            _ => return Err(format!("unknown setting `{}`", key)),
+        }
+
+        return Ok(());
+    }
+}
+
+impl fmt::Display for Equation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "(mass / {} rounded {}) - {}, cutoff {}", self.divisor, self.rounding.name(), self.subtrahend, self.cutoff);
+    }
+}
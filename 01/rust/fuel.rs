@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+
+use crate::equation::Equation;
+
+// Masses whose fuel total has been worked out are remembered up to this
+// many, so the cache can't outgrow a manifest too big to hold in memory.
+const CACHE_LIMIT: usize = 1 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FuelError {
+    // the fuel for this mass, or the sum of its fuel-for-fuel chain, doesn't
+    // fit in an i64
+    Overflow(i64),
+    // a total across modules doesn't fit in an i64
+    TotalOverflow,
+    // the fuel for this mass is above the cutoff but no less than the mass,
+    // so fuel for fuel would never run out
+    Diverges { mass: i64, fuel: i64 },
+}
+
+impl fmt::Display for FuelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            FuelError::Overflow(mass) => write!(f, "fuel for mass {} overflows a 64-bit integer", mass),
+            FuelError::TotalOverflow => write!(f, "total fuel overflows a 64-bit integer (try --wide)"),
+            FuelError::Diverges { mass, fuel } => {
+                write!(f, "fuel for mass {} is {}, no less than the mass, so fuel for fuel never runs out", mass, fuel)
+            },
+        };
+    }
+}
+
+impl Error for FuelError {}
+
+pub fn fuel_req(mass: &i64, equation: &Equation) -> Result<i64, FuelError> {
+    return equation.fuel(mass).ok_or(FuelError::Overflow(*mass));
+}
+
+// The fuel needed to carry `mass` if it's above the cutoff. Every step has to
+// be smaller than the one before, so a chain of steps always ends; an
+// equation that doesn't shrink the mass is an error rather than a cut short
+// chain.
+fn step(mass: i64, equation: &Equation) -> Result<Option<i64>, FuelError> {
+    return match fuel_req(&mass, equation)? {
+        x if x <= equation.cutoff => Ok(None),
+        x if x >= mass => Err(FuelError::Diverges { mass, fuel: x }),
+        x => Ok(Some(x)),
+    };
+}
+
+pub fn fuel_req_recursive(mass: &i64, equation: &Equation) -> Result<i64, FuelError> {
+
+    let mut total: i64 = 0;
+    let mut carried = *mass;
+
+    while let Some(fuel) = step(carried, equation)? {
+        total = total.checked_add(fuel).ok_or(FuelError::Overflow(*mass))?;
+        carried = fuel;
+    }
+
+    return Ok(total);
+}
+
+// Every step `fuel_req_recursive` adds up: the fuel for the mass, then the
+// fuel for that fuel, and so on.
+pub fn fuel_chain(mass: &i64, equation: &Equation) -> Result<Vec<i64>, FuelError> {
+
+    let mut chain = vec![];
+    let mut carried = *mass;
+
+    while let Some(fuel) = step(carried, equation)? {
+        chain.push(fuel);
+        carried = fuel;
+    }
+
+    return Ok(chain);
+}
+
+// `fuel_req_recursive` for one equation, remembering the total for every
+// mass met along the way. Chains from different modules soon run into the
+// same small masses, so later modules mostly stop early on a cached total.
+pub struct Cache<'a> {
+    equation: &'a Equation,
+    totals: HashMap<i64, i64>,
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl<'a> Cache<'a> {
+
+    pub fn new(equation: &'a Equation) -> Cache<'a> {
+        return Cache { equation, totals: HashMap::new(), hits: 0, misses: 0 };
+    }
+
+    pub fn fuel_req_recursive(&mut self, mass: &i64) -> Result<i64, FuelError> {
+
+        // masses down the chain not cached yet, each with the fuel it needs
+        let mut uncached = vec![];
+        let mut carried = *mass;
+
+        let mut total = loop {
+            if let Some(total) = self.totals.get(&carried) {
+                self.hits += 1;
+                break *total;
+            }
+            self.misses += 1;
+            match step(carried, self.equation)? {
+                Some(fuel) => {
+                    uncached.push((carried, fuel));
+                    carried = fuel;
+                },
+                None => {
+                    self.remember(carried, 0);
+                    break 0;
+                },
+            }
+        };
+
+        for (carried, fuel) in uncached.into_iter().rev() {
+            total = total.checked_add(fuel).ok_or(FuelError::Overflow(*mass))?;
+            self.remember(carried, total);
+        }
+
+        return Ok(total);
+    }
+
+    fn remember(&mut self, mass: i64, total: i64) {
+        if self.totals.len() < CACHE_LIMIT {
+            self.totals.insert(mass, total);
+        }
+    }
+}
+
+// A running total kept in an i128, which either has to stay within i64 or,
+// when `wide`, may use the full range.
+pub struct Total {
+    value: i128,
+    wide: bool,
+}
+
+impl Total {
+
+    pub fn new(wide: bool) -> Total {
+        return Total { value: 0, wide };
+    }
+
+    pub fn add(&mut self, amount: i64) -> Result<(), FuelError> {
+
+        let value = self.value.checked_add(amount as i128).ok_or(FuelError::TotalOverflow)?;
+
+        if !self.wide && i64::try_from(value).is_err() {
+            return Err(FuelError::TotalOverflow);
+        }
+
+        self.value = value;
+        return Ok(());
+    }
+
+    pub fn value(&self) -> i128 {
+        return self.value;
+    }
+}
+
+pub fn total_fuel_req<I: IntoIterator<Item = i64>>(
+    masses: I,
+    equation: &Equation,
+    wide: bool,
+    fuel_fn: fn(&i64, &Equation) -> Result<i64, FuelError>,
+) -> Result<i128, FuelError> {
+
+    let mut total = Total::new(wide);
+
+    for mass in masses {
+        total.add(fuel_fn(&mass, equation)?)?;
+    }
+
+    return Ok(total.value());
+}
@@ -1,36 +1,183 @@
+mod equation;
+mod fuel;
+mod manifest;
+mod report;
+
+use std::env;
+use std::fs;
 use std::io::prelude::*;
 use std::io;
 
-fn read_input() -> Vec<i64> {
-    return io::stdin().lock()
-        .lines()
-        .map(|l| l.unwrap().parse::<i64>().unwrap())
-        .collect::<Vec<_>>();
+use equation::Equation;
+use fuel::fuel_req;
+use fuel::fuel_req_recursive;
+use fuel::Cache;
+use fuel::Total;
+use manifest::Manifest;
+use manifest::Module;
+
+fn fail(errors: &[String]) -> ! {
+    for error in errors {
+        eprintln!("{}", error);
+    }
+    std::process::exit(1);
 }
 
-fn fuel_req(mass: &i64) -> i64 { (mass / 3) - 2 }
+fn read_input() -> Manifest {
 
-fn fuel_req_recursive(mass: &i64) -> i64 { 
-    return match fuel_req(mass) {
-        x if x <= 0 => 0,
-        v => v + fuel_req_recursive(&v),
+    let mut input = String::new();
+    io::stdin().lock().read_to_string(&mut input).unwrap();
+
+    return match Manifest::parse(&input) {
+        Ok(manifest) => manifest,
+        Err(errors) => fail(&errors.iter().map(|e| e.to_string()).collect::<Vec<_>>()),
     };
 }
 
-fn total_fuel_req(masses: &Vec<i64>, fuel_fn: fn(&i64) -> i64) -> i64 {
-    return masses.iter()
-        .map(fuel_fn)
-        .sum();
+struct Options {
+    equation: Equation,
+    // keep totals in i128 instead of failing once they outgrow an i64
+    wide: bool,
+    // only pays off when chains are long, e.g. with a divisor of 1, and
+    // modules run into the same masses
+    cache: bool,
+    verbose: bool,
+}
+
+// Splits `--equation <file>`, `--<setting> <value>` and the `--wide`,
+// `--cache` and `-v` flags off the positional arguments. Settings given
+// on the command line override the ones in the file, wherever they appear.
+fn parse_args(args: &[String]) -> (Options, Vec<String>) {
+
+    let mut positional = vec![];
+    let mut settings = vec![];
+    let mut file = None;
+    let mut wide = false;
+    let mut cache = false;
+    let mut verbose = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.strip_prefix("--") {
+            _ if arg == "-v" => verbose = true,
+            Some("wide") => wide = true,
+            Some("cache") => cache = true,
+            Some(key) => {
+                let value = match args.next() {
+                    Some(value) => value.clone(),
+                    None => fail(&[format!("--{} needs a value", key)]),
+                };
+                if key == "equation" {
+                    file = Some(value);
+                } else {
+                    settings.push((key.to_string(), value));
+                }
+            },
+            None => positional.push(arg.clone()),
+        }
+    }
+
+    let mut equation = match file {
+        Some(path) => {
+            let text = fs::read_to_string(&path).unwrap_or_else(|e| fail(&[format!("{}: {}", path, e)]));
+            equation::STANDARD.configure(&text)
+                .unwrap_or_else(|errors| fail(&errors.iter().map(|e| format!("{}: {}", path, e)).collect::<Vec<_>>()))
+        },
+        None => equation::STANDARD,
+    };
+
+    for (key, value) in settings {
+        if let Err(e) = equation.set(&key, &value) {
+            fail(&[format!("--{}: {}", key, e)]);
+        }
+    }
+
+    if let Err(e) = equation.validate() {
+        fail(&[e]);
+    }
+
+    return (Options { equation, wide, cache, verbose }, positional);
+}
+
+// Works out both parts a line at a time, so the manifest never has to be
+// held in memory. Every line is still parsed, so that all the errors in the
+// manifest can be reported together.
+fn stream(options: &Options) -> (i128, i128) {
+
+    let equation = &options.equation;
+
+    let mut part1 = Total::new(options.wide);
+    let mut part2 = Total::new(options.wide);
+    let mut cache = Cache::new(equation);
+    let mut errors = vec![];
+    let mut modules = 0;
+
+    for (n, line) in io::stdin().lock().lines().enumerate() {
+
+        let module = match Module::parse(n + 1, &line.unwrap()) {
+            Ok(Some(module)) => module,
+            Ok(None) => continue,
+            Err(e) => {
+                errors.push(e.to_string());
+                continue;
+            },
+        };
+
+        let fuel = fuel_req(&module.mass, equation)
+            .and_then(|fuel| match options.cache {
+                true => cache.fuel_req_recursive(&module.mass),
+                false => fuel_req_recursive(&module.mass, equation),
+            }.map(|total| (fuel, total)));
+
+        // once anything has gone wrong the totals are moot, but later modules
+        // are still checked so every error is reported
+        let result = match fuel {
+            Ok(_) if !errors.is_empty() => continue,
+            Ok((fuel, total)) => part1.add(fuel).and_then(|_| part2.add(total)),
+            Err(e) => Err(e),
+        };
+
+        if let Err(e) = result {
+            errors.push(format!("line {}: {}", n + 1, e));
+            continue;
+        }
+
+        modules += 1;
+    }
+
+    if !errors.is_empty() {
+        fail(&errors);
+    }
+
+    if options.verbose {
+        eprintln!("{} modules", modules);
+        if options.cache {
+            eprintln!("fuel cache: {} hits, {} misses", cache.hits, cache.misses);
+        }
+    }
+
+    return (part1.value(), part2.value());
 }
 
 fn main() {
 
-    let masses = read_input();
+    let (options, args) = parse_args(&env::args().skip(1).collect::<Vec<_>>());
 
-    let part1 = total_fuel_req(&masses, fuel_req);
+    // report [table|csv|json]
+    if args.first().map(|s| s.as_str()) == Some("report") {
+        let format = match args.get(1) {
+            Some(name) => report::Format::from(name).expect("format must be table, csv or json"),
+            None => report::Format::Table,
+        };
+        match report::build(&read_input(), &options.equation) {
+            Ok(report) => print!("{}", report::render(&report, format)),
+            Err(errors) => fail(&errors),
+        }
+        return;
+    }
 
-    let part2 = total_fuel_req(&masses, fuel_req_recursive);
+    let (part1, part2) = stream(&options);
 
     println!("part 1: {}", part1);
     println!("part 2: {}", part2);
-}
\ No newline at end of file
+}
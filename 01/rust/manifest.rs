@@ -0,0 +1,103 @@
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Module {
+    pub name: Option<String>,
+    pub mass: i64,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManifestErrorKind {
+    NotANumber(String),
+    Negative(i64),
+    EmptyName,
+    MissingMass,
+}
+
+#[derive(Debug)]
+pub struct ManifestError {
+    line: usize,
+    kind: ManifestErrorKind,
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match &self.kind {
+            ManifestErrorKind::NotANumber(text) => write!(f, "line {}: mass `{}` is not a number", self.line, text),
+            ManifestErrorKind::Negative(mass) => write!(f, "line {}: mass {} is negative", self.line, mass),
+            ManifestErrorKind::EmptyName => write!(f, "line {}: module name before `:` is empty", self.line),
+            ManifestErrorKind::MissingMass => write!(f, "line {}: module has a name but no mass", self.line),
+        };
+    }
+}
+
+impl Error for ManifestError {}
+
+pub struct Manifest {
+    pub modules: Vec<Module>,
+}
+
+impl Module {
+
+    // Parses one manifest line, numbered from 1. Blank and comment-only lines
+    // hold no module.
+    pub fn parse(line: usize, text: &str) -> Result<Option<Module>, ManifestError> {
+
+        let content = text.split('#').next().unwrap().trim();
+
+        if content.is_empty() {
+            return Ok(None);
+        }
+
+        let (name, mass) = match content.find(':') {
+            Some(i) => (Some(content[..i].trim()), content[i + 1..].trim()),
+            None => (None, content),
+        };
+
+        if name == Some("") {
+            return Err(ManifestError { line, kind: ManifestErrorKind::EmptyName });
+        }
+
+        if mass.is_empty() {
+            return Err(ManifestError { line, kind: ManifestErrorKind::MissingMass });
+        }
+
+        return match mass.parse::<i64>() {
+            Ok(m) if m < 0 => Err(ManifestError { line, kind: ManifestErrorKind::Negative(m) }),
+            Ok(m) => Ok(Some(Module { name: name.map(|n| n.to_string()), mass: m, line })),
+            Err(_) => Err(ManifestError { line, kind: ManifestErrorKind::NotANumber(mass.to_string()) }),
+        };
+    }
+}
+
+impl Manifest {
+
+    // One module per line, either a bare mass or `name: mass`. Anything after
+    // a `#` is a comment, and blank lines are skipped. Every bad line is
+    // reported, not just the first.
+    pub fn parse(text: &str) -> Result<Manifest, Vec<ManifestError>> {
+
+        let mut modules = vec![];
+        let mut errors = vec![];
+
+        for (n, line) in text.lines().enumerate() {
+            match Module::parse(n + 1, line) {
+                Ok(Some(module)) => modules.push(module),
+                Ok(None) => {},
+                Err(e) => errors.push(e),
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        return Ok(Manifest { modules });
+    }
+
+    pub fn masses(&self) -> Vec<i64> {
+        return self.modules.iter().map(|m| m.mass).collect();
+    }
+}
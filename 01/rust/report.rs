@@ -0,0 +1,220 @@
+use crate::equation::Equation;
+use crate::fuel;
+use crate::fuel::FuelError;
+use crate::manifest;
+use crate::manifest::Manifest;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format { Table, Csv, Json }
+
+impl Format {
+    pub fn from(name: &str) -> Option<Format> {
+        return match name {
+            "table" => Some(Format::Table),
+            "csv" => Some(Format::Csv),
+            "json" => Some(Format::Json),
+            _ => None,
+        };
+    }
+}
+
+pub struct Module {
+    pub name: Option<String>,
+    pub mass: i64,
+    // fuel for the module's mass alone, as in part 1
+    pub fuel: i64,
+    // fuel for that fuel, then for that fuel, ...
+    pub chain: Vec<i64>,
+    // the module's share of part 2
+    pub total: i64,
+}
+
+pub struct Report {
+    pub equation: Equation,
+    pub modules: Vec<Module>,
+    // totals are never narrowed to i64, a report should always come out
+    pub mass: i128,
+    pub fuel: i128,
+    pub total: i128,
+}
+
+fn entry(module: &manifest::Module, equation: &Equation) -> Result<Module, FuelError> {
+
+    let chain = fuel::fuel_chain(&module.mass, equation)?;
+
+    return Ok(Module {
+        name: module.name.clone(),
+        mass: module.mass,
+        fuel: fuel::fuel_req(&module.mass, equation)?,
+        chain: chain.iter().skip(1).cloned().collect(),
+        total: fuel::fuel_req_recursive(&module.mass, equation)?,
+    });
+}
+
+// Every module is checked before giving up, so all the errors come back
+// together, each with the manifest line it came from.
+pub fn build(manifest: &Manifest, equation: &Equation) -> Result<Report, Vec<String>> {
+
+    let mut modules = vec![];
+    let mut errors = vec![];
+
+    for module in manifest.modules.iter() {
+        match entry(module, equation) {
+            Ok(entry) => modules.push(entry),
+            Err(e) => errors.push(format!("line {}: {}", module.line, e)),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    // every module's fuel came out, so only the sums themselves can fail
+    let masses = manifest.masses();
+    let total = |fuel_fn| fuel::total_fuel_req(masses.iter().cloned(), equation, true, fuel_fn)
+        .map_err(|e| vec![e.to_string()]);
+
+    return Ok(Report {
+        mass: masses.iter().map(|m| *m as i128).sum(),
+        fuel: total(fuel::fuel_req)?,
+        total: total(fuel::fuel_req_recursive)?,
+        equation: *equation,
+        modules,
+    });
+}
+
+fn join(chain: &[i64], separator: &str) -> String {
+    return chain.iter()
+        .map(|f| f.to_string())
+        .collect::<Vec<_>>()
+        .join(separator);
+}
+
+fn table(report: &Report) -> String {
+
+    let rows = report.modules.iter().enumerate()
+        .map(|(i, m)| [
+            m.name.clone().unwrap_or((i + 1).to_string()),
+            m.mass.to_string(),
+            m.fuel.to_string(),
+            join(&m.chain, " "),
+            m.total.to_string(),
+            if report.total == 0 {
+                String::from("-")
+            } else {
+                format!("{:.2}%", 100.0 * m.total as f64 / report.total as f64)
+            },
+        ])
+        .chain(std::iter::once([
+            String::from("total"),
+            report.mass.to_string(),
+            report.fuel.to_string(),
+            String::new(),
+            report.total.to_string(),
+            String::new(),
+        ]))
+        .collect::<Vec<_>>();
+
+    let header = ["module", "mass", "fuel", "fuel for fuel", "total", "share"];
+
+    let widths = (0..header.len())
+        .map(|c| rows.iter().map(|r| r[c].len()).chain(std::iter::once(header[c].len())).max().unwrap())
+        .collect::<Vec<_>>();
+
+    let line = |cells: &[String]| {
+        return cells.iter().zip(widths.iter()).enumerate()
+            .map(|(c, (cell, width))| match c {
+                // names and the chain read left to right, everything else is a number
+                0 | 3 => format!("{:<1$}", cell, width),
+                _ => format!("{:>1$}", cell, width),
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string();
+    };
+
+    let mut text = format!("fuel = {}\n\n", report.equation);
+    text.push_str(&line(&header.iter().map(|h| h.to_string()).collect::<Vec<_>>()));
+    text.push('\n');
+    text.push_str(&widths.iter().map(|w| "-".repeat(*w)).collect::<Vec<_>>().join("  "));
+    text.push('\n');
+
+    for (i, row) in rows.iter().enumerate() {
+        if i == rows.len() - 1 {
+            text.push_str(&widths.iter().map(|w| "-".repeat(*w)).collect::<Vec<_>>().join("  "));
+            text.push('\n');
+        }
+        text.push_str(&line(row));
+        text.push('\n');
+    }
+
+    return text;
+}
+
+fn csv(report: &Report) -> String {
+
+    let mut text = String::from("module,name,mass,fuel,fuel_for_fuel,total\n");
+
+    for (i, m) in report.modules.iter().enumerate() {
+        let name = match &m.name {
+            Some(name) if name.contains(',') || name.contains('"') => format!("\"{}\"", name.replace('"', "\"\"")),
+            Some(name) => name.clone(),
+            None => String::new(),
+        };
+        text.push_str(&format!("{},{},{},{},{},{}\n", i + 1, name, m.mass, m.fuel, join(&m.chain, ";"), m.total));
+    }
+
+    text.push_str(&format!("total,,{},{},,{}\n", report.mass, report.fuel, report.total));
+
+    return text;
+}
+
+// A JSON string literal; control characters can't appear in one unescaped.
+fn json_string(text: &str) -> String {
+
+    let mut quoted = String::from("\"");
+
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            '\u{0}'..='\u{1f}' => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            _ => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+
+    return quoted;
+}
+
+fn json(report: &Report) -> String {
+
+    let modules = report.modules.iter().enumerate()
+        .map(|(i, m)| format!(
+            "    {{\"module\": {}, \"name\": {}, \"mass\": {}, \"fuel\": {}, \"fuel_for_fuel\": [{}], \"total\": {}}}",
+            i + 1,
+            match &m.name {
+                Some(name) => json_string(name),
+                None => String::from("null"),
+            },
+            m.mass, m.fuel, join(&m.chain, ", "), m.total))
+        .collect::<Vec<_>>()
+        .join(",\n");
+
+    return format!(
+        "{{\n  \"modules\": [\n{}\n  ],\n  \"totals\": {{\"mass\": {}, \"fuel\": {}, \"total\": {}}}\n}}\n",
+        modules, report.mass, report.fuel, report.total);
+}
+
+pub fn render(report: &Report, format: Format) -> String {
+    return match format {
+        Format::Table => table(report),
+        Format::Csv => csv(report),
+        Format::Json => json(report),
+    };
+}
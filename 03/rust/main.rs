@@ -1,9 +1,64 @@
+mod metric;
+mod segment;
+mod svg;
+
 use std::collections::HashMap;
+use std::env;
+use std::fs;
 use std::io;
 use std::io::BufRead;
 
+use metric::Distance;
+use metric::Metric;
+use metric::Ranking;
+
+// Up and down are along y and forward and backward along z, the axis
+// that goes from one layer of the board to the next.
 #[derive(Debug, Clone, Copy)]
-enum Direction { Left, Right, Up, Down }
+enum Direction {
+    Left, Right, Up, Down,
+    UpLeft, UpRight, DownLeft, DownRight,
+    Forward, Backward,
+}
+
+impl Direction {
+
+    // L, R, U and D also go by W, E, N and S, which the diagonals are named
+    // after: NE, NW, SE and SW.
+    fn from(s: &str) -> Option<Direction> {
+        return match s {
+            "L" | "W" => Some(Direction::Left),
+            "R" | "E" => Some(Direction::Right),
+            "U" | "N" => Some(Direction::Up),
+            "D" | "S" => Some(Direction::Down),
+            "NW" => Some(Direction::UpLeft),
+            "NE" => Some(Direction::UpRight),
+            "SW" => Some(Direction::DownLeft),
+            "SE" => Some(Direction::DownRight),
+            "F" => Some(Direction::Forward),
+            "B" => Some(Direction::Backward),
+            _ => None,
+        };
+    }
+
+    // Where a single step goes. A diagonal step moves along both x and y
+    // but still only counts as one step.
+    fn step(&self) -> Coordinate {
+        let (x, y, z) = match self {
+            Direction::Left => (-1, 0, 0),
+            Direction::Right => (1, 0, 0),
+            Direction::Up => (0, 1, 0),
+            Direction::Down => (0, -1, 0),
+            Direction::UpLeft => (-1, 1, 0),
+            Direction::UpRight => (1, 1, 0),
+            Direction::DownLeft => (-1, -1, 0),
+            Direction::DownRight => (1, -1, 0),
+            Direction::Forward => (0, 0, 1),
+            Direction::Backward => (0, 0, -1),
+        };
+        return Coordinate::new(x, y, z);
+    }
+}
 
 #[derive(Debug)]
 struct Instruction {
@@ -11,152 +66,427 @@ struct Instruction {
     distance: u16,
 }
 
-impl Instruction {
-    fn steps(&self) -> Steps {
-        return Steps::new(self.distance, self.direction);
-    }
-}
-
 impl std::str::FromStr for Instruction {
 
     type Err = ParseInstructionError;
 
     fn from_str(s: &str) -> std::result::Result<Instruction, ParseInstructionError> {
 
-        if s.len() < 2 {
-            return Err(ParseInstructionError::new(&s));
-        }
+        // the direction is everything before the distance
+        let split = s.find(|c: char| c.is_ascii_digit()).unwrap_or(s.len());
+        let (direction, distance) = (&s[..split], &s[split..]);
 
-        let direction = match s.chars().nth(0) {
-            Some('L') => Ok(Direction::Left),
-            Some('U') => Ok(Direction::Up),
-            Some('R') => Ok(Direction::Right),
-            Some('D') => Ok(Direction::Down),
-            _ => Err(ParseInstructionError::new(&s)),
+        let direction = match Direction::from(direction) {
+            Some(direction) => Ok(direction),
+            None if direction.is_empty() => Err(ParseInstructionError::new(s, ErrorKind::MissingDirection)),
+            None => Err(ParseInstructionError::new(s, ErrorKind::UnknownDirection(direction.to_string()))),
         }?;
 
-        let distance = match s[1..].parse::<u16>() {
+        let distance = match distance.parse::<u16>() {
             Ok(x) => Ok(x),
-            _ => Err(ParseInstructionError::new(&s)),
+            _ if distance.is_empty() => Err(ParseInstructionError::new(s, ErrorKind::MissingDistance)),
+            _ if distance.chars().all(|c| c.is_ascii_digit()) => Err(ParseInstructionError::new(s, ErrorKind::DistanceTooLarge)),
+            _ => Err(ParseInstructionError::new(s, ErrorKind::InvalidDistance(distance.to_string()))),
         }?;
 
         return Ok(Instruction { direction: direction, distance: distance });
     }
 }
 
+#[derive(Debug, PartialEq, Eq)]
+enum ErrorKind {
+    MissingDirection,
+    UnknownDirection(String),
+    MissingDistance,
+    DistanceTooLarge,
+    InvalidDistance(String),
+}
+
+// Where the instruction is in the input is only known once it's been read
+// as part of a wire; wire, token and column are numbered from 1.
 #[derive(Debug)]
 struct ParseInstructionError {
     input: String,
+    kind: ErrorKind,
+    wire: usize,
+    token: usize,
+    column: usize,
 }
 
 impl ParseInstructionError {
-    fn new(input: &str) -> ParseInstructionError {
-        return ParseInstructionError { input: input.to_string() };
+
+    fn new(input: &str, kind: ErrorKind) -> ParseInstructionError {
+        return ParseInstructionError { input: input.to_string(), kind, wire: 0, token: 0, column: 0 };
+    }
+
+    fn at(self, wire: usize, token: usize, column: usize) -> ParseInstructionError {
+        return ParseInstructionError { wire, token, column, ..self };
     }
 }
 
+impl std::fmt::Display for ParseInstructionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "wire {}, instruction {} `{}` (column {}): ", self.wire, self.token, self.input, self.column)?;
+        return match &self.kind {
+            ErrorKind::MissingDirection => write!(f, "missing direction"),
+            ErrorKind::UnknownDirection(d) => write!(f, "unknown direction `{}`", d),
+            ErrorKind::MissingDistance => write!(f, "missing distance"),
+            ErrorKind::DistanceTooLarge => write!(f, "distance is larger than {}", u16::MAX),
+            ErrorKind::InvalidDistance(d) => write!(f, "distance `{}` is not a number", d),
+        };
+    }
+}
+
+impl std::error::Error for ParseInstructionError {}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct Coordinate {
     x: i64,
     y: i64,
+    z: i64,
 }
 
 impl Coordinate {
-    fn new(x: i64, y: i64) -> Coordinate {
-        return Coordinate { x, y };
+    fn new(x: i64, y: i64, z: i64) -> Coordinate {
+        return Coordinate { x, y, z };
     }
 }
 
-type Step = fn(from: &Coordinate) -> Coordinate;
-
-struct Steps {
-    steps: u16,
-    direction: Direction,
-}
-
-impl Steps {
-    fn new(steps: u16, direction: Direction) -> Steps {
-        return Steps { steps, direction };
+// Wires that stay on one layer are shown as they always were.
+impl std::fmt::Display for Coordinate {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        return match self.z {
+            0 => write!(f, "({}, {})", self.x, self.y),
+            z => write!(f, "({}, {}, {})", self.x, self.y, z),
+        };
     }
 }
 
-impl Iterator for Steps {
-    type Item = Step;
-    fn next(&mut self) -> Option<Step> {
-        if self.steps == 0 {
-            return None;
+// Parses one line of input, wire being its number from 1. Every bad
+// instruction is reported, not just the first.
+fn parse_wire(wire: usize, line: &str) -> Result<Vec<Instruction>, Vec<ParseInstructionError>> {
+
+    let mut instructions = vec![];
+    let mut errors = vec![];
+    let mut column = 1;
+
+    for (i, token) in line.split(",").enumerate() {
+        match token.parse::<Instruction>() {
+            Ok(instruction) => instructions.push(instruction),
+            Err(e) => errors.push(e.at(wire, i + 1, column)),
         }
-        self.steps -= 1;
-        return match self.direction {
-            Direction::Left => Some(|c| Coordinate::new(c.x - 1, c.y)),
-            Direction::Right => Some(|c| Coordinate::new(c.x + 1, c.y)),
-            Direction::Up => Some(|c| Coordinate::new(c.x, c.y + 1)),
-            Direction::Down => Some(|c| Coordinate::new(c.x, c.y - 1)),
-        };
+        column += token.chars().count() + 1;
     }
+
+    return if errors.is_empty() { Ok(instructions) } else { Err(errors) };
 }
 
 fn read_input() -> Vec<Vec<Instruction>> {
 
-    fn parse_instruction_vec(s: String) -> Result<Vec<Instruction>, ParseInstructionError> {
-        return s.split(",")
-            .map(|i| i.parse::<Instruction>())
-            .collect();
+    let mut paths = vec![];
+    let mut errors = vec![];
+
+    for (n, line) in io::stdin().lock().lines().enumerate() {
+        match parse_wire(n + 1, &line.unwrap()) {
+            Ok(path) => paths.push(path),
+            Err(e) => errors.extend(e),
+        }
     }
 
-    return io::stdin().lock()
-        .lines()
-        .map(|l| l.unwrap())
-        .map(parse_instruction_vec)
-        .map(|m| m.unwrap())
-        .collect();
+    if !errors.is_empty() {
+        for error in errors {
+            eprintln!("{}", error);
+        }
+        std::process::exit(1);
+    }
+
+    return paths;
 }
 
-fn coordinates<'a>(path: &'a Vec<Instruction>) -> HashMap<Coordinate, usize> {
+// Wires are labelled A, B, C, ... in input order.
+fn label(wire: usize) -> String {
+    return match wire {
+        0..=25 => ((b'A' + wire as u8) as char).to_string(),
+        _ => format!("wire {}", wire + 1),
+    };
+}
+
+// The intersection closest to the ranking's origin and the one with the
+// fewest combined steps, ties broken by position so the output is stable.
+fn best(intersections: &HashMap<Coordinate, usize>, ranking: &Ranking) -> Option<((Distance, Coordinate), (usize, Coordinate))> {
 
-    let mut coords = HashMap::new();
+    let closest = intersections.keys()
+        .map(|k| (ranking.distance(k), *k))
+        .min_by_key(|(d, k)| (*d, k.x, k.y, k.z))?;
 
-    path.iter()
-        .flat_map(|m| m.steps())
-        .scan(Coordinate::new(0, 0), | coord, step_fn | {
-            *coord = step_fn(coord);
-            return Some(*coord)
+    let fastest = intersections.iter()
+        .map(|(k, v)| (*v, *k))
+        .min_by_key(|(v, k)| (*v, k.x, k.y, k.z))?;
+
+    return Some((closest, fastest));
+}
+
+fn describe(name: &str, intersections: &HashMap<Coordinate, usize>, ranking: &Ranking) {
+    match best(intersections, ranking) {
+        Some(((d, near), (steps, quick))) => println!(
+            "{}: closest {} at {}, fewest steps {} at {}",
+            name, d, near, steps, quick),
+        None => println!("{}: no intersections", name),
+    }
+}
+
+// Points every wire passes through, with the steps of all the wires added up.
+fn shared(wires: &[Vec<segment::Segment>]) -> HashMap<Coordinate, usize> {
+    return segment::intersections(&wires[0], &wires[1]).keys()
+        .filter_map(|k| {
+            let steps = wires.iter()
+                .map(|w| segment::steps_to(w, k))
+                .collect::<Option<Vec<_>>>()?;
+            return Some((*k, steps.iter().sum()));
         })
-        .enumerate()
-        .for_each(|(v, k)| {
-            if !coords.contains_key(&k) {
-                coords.insert(k, v);
-            }
-        });
+        .collect();
+}
 
-    return coords;
+struct Options {
+    // also report the points shared by every wire
+    all: bool,
+    ranking: Ranking,
+    positional: Vec<String>,
+}
+
+// `--all`, `--metric <manhattan|chebyshev|euclidean>` and `--origin <x,y[,z]>`
+// can go anywhere among the other arguments.
+fn parse_args(args: &[String]) -> Options {
+
+    let mut options = Options { all: false, ranking: Ranking::default(), positional: vec![] };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--all" => options.all = true,
+            "--metric" => {
+                let name = args.next().expect("--metric needs a value");
+                options.ranking.metric = Metric::from(name)
+                    .expect("metric must be manhattan, chebyshev or euclidean");
+            },
+            "--origin" => {
+                let value = args.next().expect("--origin needs a value");
+                let parts = value.split(',')
+                    .map(|p| p.trim().parse::<i64>().expect("origin must be x,y or x,y,z"))
+                    .collect::<Vec<_>>();
+                options.ranking.origin = match parts[..] {
+                    [x, y] => Coordinate::new(x, y, 0),
+                    [x, y, z] => Coordinate::new(x, y, z),
+                    _ => panic!("origin must be x,y or x,y,z"),
+                };
+            },
+            _ => options.positional.push(arg.clone()),
+        }
+    }
+
+    return options;
 }
 
 fn main() {
 
+    let options = parse_args(&env::args().skip(1).collect::<Vec<_>>());
+    let args = &options.positional;
+    let all = options.all;
+    let ranking = &options.ranking;
+
     let paths = read_input();
 
-    let [first, second] = match &paths[..] {
-        [a, b] => [coordinates(a), coordinates(b)],
-        _ => panic!("expected 2 paths from input"),
-    };
+    // svg [file], to stdout without a file
+    if args.first().map(|s| s.as_str()) == Some("svg") {
+        let svg = svg::render(&paths, ranking);
+        match args.get(1) {
+            Some(path) => fs::write(path, svg).unwrap(),
+            None => print!("{}", svg),
+        }
+        return;
+    }
 
-    let mut intersections = HashMap::new();
+    if paths.len() < 2 {
+        eprintln!("expected at least 2 wires, one per line, got {}", paths.len());
+        std::process::exit(1);
+    }
+
+    let wires = paths.iter()
+        .map(|p| segment::segments(p))
+        .collect::<Vec<_>>();
+
+    if wires.len() == 2 && !all {
+
+        let intersections = segment::intersections(&wires[0], &wires[1]);
 
-    for (key, val) in first {
-        if let Some(v) = second.get(&key) {
-            intersections.insert(key, val + v + 2);
+        match best(&intersections, ranking) {
+            Some(((part1, _), (part2, _))) => {
+                println!("part 1: {}", part1);
+                println!("part 2: {}", part2);
+            },
+            None => describe(&format!("{} x {}", label(0), label(1)), &intersections, ranking),
         }
+
+        return;
     }
 
-    let part1 = intersections.keys()
-        .map(|k| k.x + k.y)
-        .min()
-        .unwrap();
+    for i in 0..wires.len() {
+        for j in (i + 1)..wires.len() {
+            let name = format!("{} x {}", label(i), label(j));
+            describe(&name, &segment::intersections(&wires[i], &wires[j]), ranking);
+        }
+    }
 
-    let part2 = intersections.values().min().unwrap();
+    if all {
+        describe("all wires", &shared(&wires), ranking);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
 
-    println!("part 1: {}", part1);
+    fn wire(s: &str) -> Vec<segment::Segment> {
+        let path = s.split(",")
+            .map(|i| i.parse::<Instruction>().unwrap())
+            .collect::<Vec<_>>();
+        return segment::segments(&path);
+    }
+
+    fn ranking(metric: Metric, x: i64, y: i64) -> Ranking {
+        return Ranking { metric, origin: Coordinate::new(x, y, 0) };
+    }
+
+    // The same pair of wires mirrored into each quadrant, crossing at (±3, ±4).
+    fn quadrants() -> Vec<(Coordinate, HashMap<Coordinate, usize>)> {
+        let cases = [
+            ("R3,U8", "U4,R8", 3, 4),
+            ("L3,U8", "U4,L8", -3, 4),
+            ("L3,D8", "D4,L8", -3, -4),
+            ("R3,D8", "D4,R8", 3, -4),
+        ];
+        return cases.iter()
+            .map(|(a, b, x, y)| (Coordinate::new(*x, *y, 0), segment::intersections(&wire(a), &wire(b))))
+            .collect();
+    }
+
+    #[test]
+    fn crossings_in_every_quadrant() {
+        for (expected, intersections) in quadrants() {
+            let ((distance, closest), (steps, fastest)) = best(&intersections, &Ranking::default()).unwrap();
+            assert_eq!(closest, expected);
+            assert_eq!(fastest, expected);
+            assert_eq!(distance.to_string(), "7");
+            assert_eq!(steps, 14);
+        }
+    }
 
-    println!("part 2: {}", part2);
-}
\ No newline at end of file
+    #[test]
+    fn metrics_in_every_quadrant() {
+        for (expected, intersections) in quadrants() {
+            for (metric, shown) in [(Metric::Manhattan, "7"), (Metric::Chebyshev, "4"), (Metric::Euclidean, "5.000")].iter() {
+                let ((distance, closest), _) = best(&intersections, &ranking(*metric, 0, 0)).unwrap();
+                assert_eq!(closest, expected);
+                assert_eq!(distance.to_string(), *shown);
+            }
+        }
+    }
+
+    #[test]
+    fn metrics_disagree() {
+        let mut intersections = HashMap::new();
+        intersections.insert(Coordinate::new(6, 0, 0), 1);
+        intersections.insert(Coordinate::new(-4, -4, 0), 2);
+
+        let ((_, closest), _) = best(&intersections, &ranking(Metric::Manhattan, 0, 0)).unwrap();
+        assert_eq!(closest, Coordinate::new(6, 0, 0));
+
+        let ((_, closest), _) = best(&intersections, &ranking(Metric::Chebyshev, 0, 0)).unwrap();
+        assert_eq!(closest, Coordinate::new(-4, -4, 0));
+
+        let ((distance, closest), _) = best(&intersections, &ranking(Metric::Euclidean, 0, 0)).unwrap();
+        assert_eq!(closest, Coordinate::new(-4, -4, 0));
+        assert_eq!(distance.to_string(), "5.657");
+    }
+
+    #[test]
+    fn negative_coordinates_count() {
+        let mut intersections = HashMap::new();
+        intersections.insert(Coordinate::new(-30, -40, 0), 100);
+        intersections.insert(Coordinate::new(2, 3, 0), 200);
+
+        let ((distance, closest), (steps, fastest)) = best(&intersections, &Ranking::default()).unwrap();
+        assert_eq!((closest, distance.to_string()), (Coordinate::new(2, 3, 0), "5".to_string()));
+        assert_eq!((fastest, steps), (Coordinate::new(-30, -40, 0), 100));
+    }
+
+    #[test]
+    fn origin_moves_the_ranking() {
+        let mut intersections = HashMap::new();
+        intersections.insert(Coordinate::new(-30, -40, 0), 100);
+        intersections.insert(Coordinate::new(2, 3, 0), 200);
+
+        let ((distance, closest), _) = best(&intersections, &ranking(Metric::Manhattan, -30, -39)).unwrap();
+        assert_eq!(closest, Coordinate::new(-30, -40, 0));
+        assert_eq!(distance.to_string(), "1");
+    }
+
+    #[test]
+    fn euclidean_at_the_extremes() {
+        // the squares of these differences add up past 128 bits
+        let low = Coordinate::new(i64::MIN, i64::MIN, i64::MIN);
+        let high = Coordinate::new(i64::MAX, i64::MAX, i64::MAX);
+        let near = Coordinate::new(i64::MAX, i64::MAX, i64::MAX - 1);
+        let far = Metric::Euclidean.distance(&low, &high);
+        assert!(Metric::Euclidean.distance(&low, &near) < far);
+        assert!(Metric::Euclidean.distance(&low, &low) < far);
+        let shown = far.to_string().parse::<f64>().unwrap();
+        assert!((shown / (3f64.sqrt() * 2f64.powi(64)) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn diagonals() {
+        let intersections = segment::intersections(&wire("NE6"), &wire("R6,NW6"));
+        assert_eq!(intersections.get(&Coordinate::new(3, 3, 0)), Some(&12));
+
+        // these cross between grid points, where neither wire ever is
+        assert!(segment::intersections(&wire("NE5"), &wire("R5,NW5")).is_empty());
+    }
+
+    #[test]
+    fn layers() {
+        let intersections = segment::intersections(&wire("R5,F3,U5"), &wire("F3,U2,R8"));
+        let ((distance, closest), (steps, _)) = best(&intersections, &Ranking::default()).unwrap();
+        assert_eq!(closest, Coordinate::new(5, 2, 3));
+        assert_eq!(closest.to_string(), "(5, 2, 3)");
+        assert_eq!(distance.to_string(), "10");
+        assert_eq!(steps, 20);
+
+        // the same wires a layer apart never meet
+        assert!(segment::intersections(&wire("R5,U5"), &wire("F1,U2,R8")).is_empty());
+    }
+
+    #[test]
+    fn parse_errors() {
+        let errors = parse_wire(2, "R8,X5,U,,L70000,D1x,NE3").unwrap_err()
+            .iter().map(|e| e.to_string()).collect::<Vec<_>>();
+        assert_eq!(errors, vec![
+            "wire 2, instruction 2 `X5` (column 4): unknown direction `X`",
+            "wire 2, instruction 3 `U` (column 7): missing distance",
+            "wire 2, instruction 4 `` (column 9): missing direction",
+            "wire 2, instruction 5 `L70000` (column 10): distance is larger than 65535",
+            "wire 2, instruction 6 `D1x` (column 17): distance `1x` is not a number",
+        ]);
+    }
+
+    #[test]
+    fn options() {
+        let args = ["svg", "--metric", "euclidean", "out.svg", "--origin", "-3,4", "--all"]
+            .iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let options = parse_args(&args);
+        assert!(options.all);
+        assert_eq!(options.ranking, ranking(Metric::Euclidean, -3, 4));
+        assert_eq!(options.positional, vec!["svg", "out.svg"]);
+    }
+}
@@ -0,0 +1,78 @@
+use std::fmt;
+
+use crate::Coordinate;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Metric { Manhattan, Chebyshev, Euclidean }
+
+impl Metric {
+
+    pub fn from(name: &str) -> Option<Metric> {
+        return match name {
+            "manhattan" => Some(Metric::Manhattan),
+            "chebyshev" => Some(Metric::Chebyshev),
+            "euclidean" => Some(Metric::Euclidean),
+            _ => None,
+        };
+    }
+
+    pub fn distance(&self, from: &Coordinate, to: &Coordinate) -> Distance {
+
+        // differences of far apart coordinates, and their squares, don't
+        // fit in an i64, but each square does fit in a u128; the sum of
+        // three squares can carry past it, so the carry is kept too
+        let dx = to.x.abs_diff(from.x) as u128;
+        let dy = to.y.abs_diff(from.y) as u128;
+        let dz = to.z.abs_diff(from.z) as u128;
+
+        let value = match self {
+            Metric::Manhattan => (0, dx + dy + dz),
+            Metric::Chebyshev => (0, dx.max(dy).max(dz)),
+            Metric::Euclidean => {
+                let (xy, c1) = (dx * dx).overflowing_add(dy * dy);
+                let (xyz, c2) = xy.overflowing_add(dz * dz);
+                (c1 as u8 + c2 as u8, xyz)
+            },
+        };
+
+        return Distance { value, metric: *self };
+    }
+}
+
+// Euclidean distances are kept squared, so they still compare exactly and
+// only turn into a float when shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Distance {
+    // the carry out of 128 bits first, so it orders like the whole number
+    value: (u8, u128),
+    metric: Metric,
+}
+
+impl fmt::Display for Distance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (carry, low) = self.value;
+        return match self.metric {
+            Metric::Euclidean => write!(f, "{:.3}", (carry as f64 * 2f64.powi(128) + low as f64).sqrt()),
+            _ => write!(f, "{}", low),
+        };
+    }
+}
+
+// How intersections are ranked for part 1: by distance from `origin`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ranking {
+    pub metric: Metric,
+    pub origin: Coordinate,
+}
+
+impl Ranking {
+    pub fn distance(&self, c: &Coordinate) -> Distance {
+        return self.metric.distance(&self.origin, c);
+    }
+}
+
+impl Default for Ranking {
+    fn default() -> Ranking {
+        return Ranking { metric: Metric::Manhattan, origin: Coordinate::new(0, 0, 0) };
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+use crate::Coordinate;
+use crate::Instruction;
+
+// One straight run of a wire, together with how many steps the wire had
+// already taken when the run started.
+#[derive(Debug, Clone, Copy)]
+pub struct Segment {
+    start: Coordinate,
+    // a single step, each axis one of -1, 0 or 1
+    step: Coordinate,
+    length: i64,
+    offset: usize,
+}
+
+impl Segment {
+
+    fn horizontal(&self) -> bool {
+        return self.step.y == 0 && self.step.z == 0;
+    }
+
+    fn vertical(&self) -> bool {
+        return self.step.x == 0 && self.step.z == 0;
+    }
+
+    // Runs along x or y on a single layer are the common case, and the only
+    // ones the sweep deals with.
+    fn flat(&self) -> bool {
+        return self.horizontal() || self.vertical();
+    }
+
+    // the coordinate in the layer that doesn't change along a flat segment
+    fn fixed(&self) -> i64 {
+        return if self.horizontal() { self.start.y } else { self.start.x };
+    }
+
+    fn min(&self) -> i64 {
+        let end = self.point(self.length);
+        return if self.horizontal() { self.start.x.min(end.x) } else { self.start.y.min(end.y) };
+    }
+
+    fn max(&self) -> i64 {
+        let end = self.point(self.length);
+        return if self.horizontal() { self.start.x.max(end.x) } else { self.start.y.max(end.y) };
+    }
+
+    // the point `steps` steps in
+    fn point(&self, steps: i64) -> Coordinate {
+        return Coordinate::new(
+            self.start.x + self.step.x * steps,
+            self.start.y + self.step.y * steps,
+            self.start.z + self.step.z * steps);
+    }
+
+    // How many steps in `c` is, if it's on the line the segment runs along,
+    // even beyond either end.
+    fn position(&self, c: &Coordinate) -> Option<i64> {
+
+        let mut steps = None;
+
+        for (d, step) in [(c.x - self.start.x, self.step.x), (c.y - self.start.y, self.step.y), (c.z - self.start.z, self.step.z)].iter() {
+            if *step == 0 {
+                if *d != 0 {
+                    return None;
+                }
+            } else if steps.is_some() && steps != Some(d * step) {
+                return None;
+            } else {
+                steps = Some(d * step);
+            }
+        }
+
+        return steps;
+    }
+
+    fn along(&self, c: &Coordinate) -> Option<i64> {
+        return self.position(c).filter(|steps| 0 <= *steps && *steps <= self.length);
+    }
+
+    fn contains(&self, c: &Coordinate) -> bool {
+        return self.along(c).is_some();
+    }
+
+    // Steps the wire has taken on reaching `c`, which must be on the segment.
+    fn steps_to(&self, c: &Coordinate) -> usize {
+        return self.offset + self.along(c).unwrap() as usize;
+    }
+}
+
+pub fn segments(path: &[Instruction]) -> Vec<Segment> {
+
+    let mut segments = vec![];
+    let mut start = Coordinate::new(0, 0, 0);
+    let mut offset = 0;
+
+    for instruction in path.iter() {
+
+        let segment = Segment {
+            start,
+            step: instruction.direction.step(),
+            length: instruction.distance as i64,
+            offset,
+        };
+
+        if segment.length > 0 {
+            segments.push(segment);
+        }
+
+        start = segment.point(segment.length);
+        offset += instruction.distance as usize;
+    }
+
+    return segments;
+}
+
+// Steps a wire takes to first reach `c`, if it ever does. The origin only
+// counts as reached if the wire comes back to it.
+pub fn steps_to(wire: &[Segment], c: &Coordinate) -> Option<usize> {
+    return wire.iter()
+        .filter(|s| s.contains(c))
+        .map(|s| s.steps_to(c))
+        .filter(|steps| *steps > 0)
+        .min();
+}
+
+// Records a crossing, keeping the lowest combined step count for each point.
+// Where a wire comes back through a point, the step count of its first visit
+// is the smallest one on offer, so this matches taking each wire's first
+// visit. The origin only counts if both wires come back to it.
+fn record(found: &mut HashMap<Coordinate, usize>, c: Coordinate, a: &Segment, b: &Segment) {
+
+    let (a, b) = (a.steps_to(&c), b.steps_to(&c));
+
+    if a == 0 || b == 0 {
+        return;
+    }
+
+    let steps = found.entry(c).or_insert(usize::MAX);
+    *steps = (*steps).min(a + b);
+}
+
+// The points two segments running the same way or opposite ways share.
+fn overlap(a: &Segment, b: &Segment) -> Vec<Coordinate> {
+
+    let (first, last) = match (a.position(&b.start), a.position(&b.point(b.length))) {
+        (Some(first), Some(last)) => (first.min(last), first.max(last)),
+        _ => return vec![],
+    };
+
+    return (first.max(0)..=last.min(a.length)).map(|steps| a.point(steps)).collect();
+}
+
+// Where two segments meet, however they run. Segments that aren't parallel
+// are solved for on two axes along which they're independent and then
+// checked on all three; a crossing between grid points, as diagonals can
+// have, isn't one the wires share.
+fn meet(a: &Segment, b: &Segment) -> Vec<Coordinate> {
+
+    let u = [a.step.x, a.step.y, a.step.z];
+    let v = [b.step.x, b.step.y, b.step.z];
+
+    if u == v || u == [-v[0], -v[1], -v[2]] {
+        return overlap(a, b);
+    }
+
+    let d = [b.start.x - a.start.x, b.start.y - a.start.y, b.start.z - a.start.z];
+
+    for (i, j) in [(0, 1), (0, 2), (1, 2)].iter() {
+        let (i, j) = (*i, *j);
+        let det = v[i] * u[j] - u[i] * v[j];
+        if det == 0 {
+            continue;
+        }
+        let steps = v[i] * d[j] - d[i] * v[j];
+        if steps % det != 0 || steps / det < 0 || steps / det > a.length {
+            return vec![];
+        }
+        let c = a.point(steps / det);
+        return if b.contains(&c) { vec![c] } else { vec![] };
+    }
+
+    return vec![];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Event {
+    // ordered so that a vertical segment at the very end of a horizontal one
+    // still sees it
+    Enter,
+    Query,
+    Leave,
+}
+
+// Every point where the two wires meet, with the fewest combined steps both
+// wires take to get there.
+//
+// Perpendicular crossings of flat segments are found with a sweep from left
+// to right: each horizontal segment is active between its ends, and each
+// vertical segment looks up the other wire's active segments on its layer
+// within its own span of y. Wires that run along each other share every
+// point of the overlap, which is rare enough that those points are simply
+// listed. Diagonals and runs from one layer to another are rarer still, and
+// are checked against every segment of the other wire.
+pub fn intersections(first: &[Segment], second: &[Segment]) -> HashMap<Coordinate, usize> {
+
+    let wires = [first, second];
+    let mut found = HashMap::new();
+
+    let mut events = vec![];
+    for (w, wire) in wires.iter().enumerate() {
+        for (i, segment) in wire.iter().enumerate() {
+            if !segment.flat() {
+                continue;
+            }
+            if segment.horizontal() {
+                events.push((segment.min(), Event::Enter, w, i));
+                events.push((segment.max(), Event::Leave, w, i));
+            } else {
+                events.push((segment.fixed(), Event::Query, w, i));
+            }
+        }
+    }
+    events.sort();
+
+    // horizontal segments the sweep is currently over, per wire, by layer and y
+    let mut active: [BTreeMap<(i64, i64, usize), ()>; 2] = [BTreeMap::new(), BTreeMap::new()];
+
+    for (x, event, w, i) in events {
+        let segment = &wires[w][i];
+        let z = segment.start.z;
+        match event {
+            Event::Enter => { active[w].insert((z, segment.fixed(), i), ()); },
+            Event::Leave => { active[w].remove(&(z, segment.fixed(), i)); },
+            Event::Query => {
+                let other = 1 - w;
+                for ((_, y, j), _) in active[other].range((z, segment.min(), 0)..=(z, segment.max(), usize::MAX)) {
+                    let c = Coordinate::new(x, *y, z);
+                    let (a, b) = if w == 0 { (segment, &wires[1][*j]) } else { (&wires[0][*j], segment) };
+                    record(&mut found, c, a, b);
+                }
+            },
+        }
+    }
+
+    // flat runs of the two wires along the same line
+    let mut lines: HashMap<(bool, i64, i64), Vec<&Segment>> = HashMap::new();
+    for segment in second.iter().filter(|s| s.flat()) {
+        lines.entry((segment.horizontal(), segment.fixed(), segment.start.z)).or_default().push(segment);
+    }
+
+    for a in first.iter().filter(|s| s.flat()) {
+        for b in lines.get(&(a.horizontal(), a.fixed(), a.start.z)).into_iter().flatten() {
+            for c in overlap(a, b) {
+                record(&mut found, c, a, b);
+            }
+        }
+    }
+
+    // everything else
+    for a in first.iter() {
+        for b in second.iter().filter(|b| !a.flat() || !b.flat()) {
+            for c in meet(a, b) {
+                record(&mut found, c, a, b);
+            }
+        }
+    }
+
+    return found;
+}
@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+
+use crate::metric::Ranking;
+use crate::segment;
+use crate::Coordinate;
+use crate::Instruction;
+
+const COLOURS: [&str; 8] = ["#e6194b", "#3cb44b", "#4363d8", "#f58231", "#911eb4", "#42d4f4", "#f032e6", "#9a6324"];
+
+// The corners of a wire, starting from the origin.
+fn corners(path: &[Instruction]) -> Vec<Coordinate> {
+
+    let mut corners = vec![Coordinate::new(0, 0, 0)];
+
+    for instruction in path.iter() {
+        let last = corners[corners.len() - 1];
+        let distance = instruction.distance as i64;
+        let step = instruction.direction.step();
+        corners.push(Coordinate::new(
+            last.x + step.x * distance,
+            last.y + step.y * distance,
+            last.z + step.z * distance));
+    }
+
+    return corners;
+}
+
+// SVG's y axis points down, the wires' points up. Layers are drawn on top of
+// each other, so moving along z doesn't show.
+fn point(c: &Coordinate) -> String {
+    return format!("{},{}", c.x, -c.y);
+}
+
+fn marker(c: &Coordinate, radius: f64, fill: &str) -> String {
+    return format!("  <circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"/>\n", c.x, -c.y, radius, fill);
+}
+
+fn label(c: &Coordinate, size: f64, text: &str) -> String {
+    return format!("  <text x=\"{}\" y=\"{}\" font-size=\"{}\" font-family=\"sans-serif\">{}</text>\n",
+        c.x as f64 + size, -c.y as f64 - size, size * 3.0, text);
+}
+
+// Draws every wire in its own colour, with the origin in black, every point
+// where two wires cross in grey, and the crossings that win part 1 and part 2
+// enlarged and labelled.
+pub fn render(paths: &[Vec<Instruction>], ranking: &Ranking) -> String {
+
+    let wires = paths.iter().map(|p| corners(p)).collect::<Vec<_>>();
+    let segments = paths.iter().map(|p| segment::segments(p)).collect::<Vec<_>>();
+
+    let mut intersections: HashMap<Coordinate, usize> = HashMap::new();
+    for i in 0..segments.len() {
+        for j in (i + 1)..segments.len() {
+            for (k, v) in segment::intersections(&segments[i], &segments[j]) {
+                let steps = intersections.entry(k).or_insert(v);
+                *steps = (*steps).min(v);
+            }
+        }
+    }
+
+    let points = wires.iter().flatten().collect::<Vec<_>>();
+    let min_x = points.iter().map(|c| c.x).min().unwrap_or(0);
+    let max_x = points.iter().map(|c| c.x).max().unwrap_or(0);
+    let min_y = points.iter().map(|c| -c.y).min().unwrap_or(0);
+    let max_y = points.iter().map(|c| -c.y).max().unwrap_or(0);
+
+    // everything is scaled to the size of the drawing, so it stays visible
+    // however long the wires are
+    let size = (max_x - min_x).max(max_y - min_y).max(1) as f64;
+    let stroke = size / 500.0;
+    let radius = size / 150.0;
+    let margin = (size / 20.0) as i64 + 1;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\n",
+        min_x - margin, min_y - margin, max_x - min_x + 2 * margin, max_y - min_y + 2 * margin);
+
+    svg.push_str(&format!(
+        "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"white\"/>\n",
+        min_x - margin, min_y - margin, max_x - min_x + 2 * margin, max_y - min_y + 2 * margin));
+
+    for (i, wire) in wires.iter().enumerate() {
+        svg.push_str(&format!(
+            "  <polyline fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" points=\"{}\"/>\n",
+            COLOURS[i % COLOURS.len()], stroke,
+            wire.iter().map(point).collect::<Vec<_>>().join(" ")));
+    }
+
+    let mut crossings = intersections.keys().collect::<Vec<_>>();
+    crossings.sort_by_key(|c| (c.x, c.y, c.z));
+
+    for c in crossings {
+        svg.push_str(&marker(c, radius, "grey"));
+    }
+
+    svg.push_str(&marker(&Coordinate::new(0, 0, 0), radius * 2.0, "black"));
+    svg.push_str(&label(&Coordinate::new(0, 0, 0), radius, "origin"));
+
+    // part 1 distances are measured from somewhere else
+    if ranking.origin != Coordinate::new(0, 0, 0) {
+        svg.push_str(&marker(&ranking.origin, radius * 2.0, "dimgrey"));
+        svg.push_str(&label(&ranking.origin, radius, "ranking origin"));
+    }
+
+    if let Some(((distance, closest), (steps, fastest))) = crate::best(&intersections, ranking) {
+        svg.push_str(&marker(&closest, radius * 2.0, "gold"));
+        svg.push_str(&label(&closest, radius, &format!("part 1: {}", distance)));
+        svg.push_str(&marker(&fastest, radius * 2.0, "darkorange"));
+        svg.push_str(&label(&fastest, radius, &format!("part 2: {}", steps)));
+    }
+
+    svg.push_str("</svg>\n");
+
+    return svg;
+}
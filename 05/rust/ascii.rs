@@ -0,0 +1,136 @@
+// Copied to the other ASCII Intcode days by ./sync-intcode. They drive the
+// processor's queues with `send_line` and `receive` rather than `run`.
+#![allow(dead_code)]
+
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+
+use crate::intcode::IntcodeProcessor;
+
+// `read` and `write` go to the processor as two separate callbacks, and both
+// need the line buffer (`read` prints a pending prompt from it), so the
+// adapter's state is thread-local rather than borrowed by either one.
+thread_local! {
+    static PENDING: RefCell<VecDeque<isize>> = const { RefCell::new(VecDeque::new()) };
+    static SCRIPT: RefCell<VecDeque<String>> = const { RefCell::new(VecDeque::new()) };
+    static LINE: RefCell<String> = const { RefCell::new(String::new()) };
+    static INTERACTIVE: Cell<bool> = const { Cell::new(false) };
+}
+
+const NEWLINE: isize = 10;
+
+pub fn encode(line: &str) -> Vec<isize> {
+    return line.chars()
+        .map(|c| c as isize)
+        .chain(std::iter::once(NEWLINE))
+        .collect();
+}
+
+// Values above 127 can't be characters and pass through as raw numbers.
+pub fn is_text(value: isize) -> bool {
+    return (0..=127).contains(&value);
+}
+
+pub fn send_line(computer: &mut IntcodeProcessor, line: &str) {
+    for code in encode(line) {
+        computer.push_input(code);
+    }
+}
+
+// Drains everything the program has printed so far into its text and the
+// raw numbers in between.
+pub fn receive(computer: &mut IntcodeProcessor) -> (String, Vec<isize>) {
+
+    let mut text = String::new();
+    let mut raw = vec![];
+
+    while let Some(value) = computer.pop_output() {
+        match is_text(value) {
+            true => text.push(value as u8 as char),
+            false => raw.push(value),
+        }
+    }
+
+    return (text, raw);
+}
+
+pub fn queue(line: &str) {
+    SCRIPT.with(|s| s.borrow_mut().push_back(line.to_string()));
+}
+
+pub fn set_interactive(interactive: bool) {
+    INTERACTIVE.with(|i| i.set(interactive));
+}
+
+fn next_line() -> String {
+
+    if let Some(line) = SCRIPT.with(|s| s.borrow_mut().pop_front()) {
+        if INTERACTIVE.with(|i| i.get()) {
+            println!("> {}", line);
+        }
+        return line;
+    }
+
+    if !INTERACTIVE.with(|i| i.get()) {
+        panic!("program requested input but no scripted lines remain");
+    }
+
+    // a prompt without a trailing newline is still waiting in the line buffer
+    let partial = LINE.with(|l| l.replace(String::new()));
+    print!("{}> ", partial);
+    io::stdout().flush().unwrap();
+
+    return match io::stdin().lock().lines().next() {
+        Some(line) => line.unwrap(),
+        None => panic!("end of input while program awaits a command"),
+    };
+}
+
+pub fn read() -> isize {
+    loop {
+        if let Some(code) = PENDING.with(|p| p.borrow_mut().pop_front()) {
+            return code;
+        }
+        let line = next_line();
+        PENDING.with(|p| p.borrow_mut().extend(encode(&line)));
+    }
+}
+
+pub fn write(value: isize) {
+    match value {
+        NEWLINE => {
+            let line = LINE.with(|l| l.replace(String::new()));
+            println!("{}", line);
+        },
+        v if is_text(v) => {
+            LINE.with(|l| l.borrow_mut().push(value as u8 as char));
+        },
+        _ => {
+            flush();
+            println!("{}", value);
+        },
+    }
+}
+
+pub fn flush() {
+    let line = LINE.with(|l| l.replace(String::new()));
+    if !line.is_empty() {
+        println!("{}", line);
+    }
+}
+
+pub fn run(program: &[isize], script: &[String], interactive: bool) {
+
+    set_interactive(interactive);
+    script.iter().for_each(|l| queue(l));
+
+    let mut computer = IntcodeProcessor::new(program);
+
+    computer.run_with(read, write);
+
+    flush();
+}
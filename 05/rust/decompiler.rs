@@ -0,0 +1,697 @@
+use std::collections::btree_map;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::instruction::Instruction;
+use crate::instruction::Mode;
+use crate::instruction::Opcode;
+use crate::instruction::Param;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operand {
+    Position(usize),
+    Immediate(isize),
+    // an offset from the relative base
+    Relative(isize),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Add(Operand, Operand, Operand),
+    Mul(Operand, Operand, Operand),
+    Input(Operand),
+    Output(Operand),
+    Jnz(Operand, Operand),
+    Jz(Operand, Operand),
+    Lt(Operand, Operand, Operand),
+    Eq(Operand, Operand, Operand),
+    Arb(Operand),
+    Halt,
+}
+
+impl Op {
+
+    // Lifts an instruction the shared decoder has already decoded at
+    // `address` into an op with its operands read from `program`.
+    fn lift(instruction: &Instruction, program: &[isize], address: usize) -> Option<Op> {
+
+        let arg = |position: usize| -> Option<Operand> {
+            let value = *program.get(address + position)?;
+            return match instruction.params()[position - 1] {
+                Param::Read(Mode::Position) if value >= 0 => Some(Operand::Position(value as usize)),
+                Param::Read(Mode::Immediate) => Some(Operand::Immediate(value)),
+                Param::Read(Mode::Relative) => Some(Operand::Relative(value)),
+                _ => None,
+            };
+        };
+
+        let dst = |position: usize| -> Option<Operand> {
+            let value = *program.get(address + position)?;
+            return match instruction.params()[position - 1] {
+                Param::Write(Mode::Position) if value >= 0 => Some(Operand::Position(value as usize)),
+                Param::Write(Mode::Relative) => Some(Operand::Relative(value)),
+                _ => None,
+            };
+        };
+
+        let op = match instruction.opcode {
+            Opcode::Add => Op::Add(arg(1)?, arg(2)?, dst(3)?),
+            Opcode::Mul => Op::Mul(arg(1)?, arg(2)?, dst(3)?),
+            Opcode::Input => Op::Input(dst(1)?),
+            Opcode::Output => Op::Output(arg(1)?),
+            Opcode::JumpIfTrue => Op::Jnz(arg(1)?, arg(2)?),
+            Opcode::JumpIfFalse => Op::Jz(arg(1)?, arg(2)?),
+            Opcode::LessThan => Op::Lt(arg(1)?, arg(2)?, dst(3)?),
+            Opcode::Equals => Op::Eq(arg(1)?, arg(2)?, dst(3)?),
+            Opcode::AdjustBase => Op::Arb(arg(1)?),
+            Opcode::Halt => Op::Halt,
+            Opcode::Custom(_) => return None,
+        };
+
+        return Some(op);
+    }
+
+    fn reads(&self) -> Vec<Operand> {
+        return match *self {
+            Op::Add(a, b, _) | Op::Mul(a, b, _) | Op::Lt(a, b, _) | Op::Eq(a, b, _) => vec![a, b],
+            Op::Jnz(a, b) | Op::Jz(a, b) => vec![a, b],
+            Op::Output(a) | Op::Arb(a) => vec![a],
+            Op::Input(_) | Op::Halt => vec![],
+        };
+    }
+
+    // Only the targets known ahead of time; relative ones depend on the base.
+    fn writes(&self) -> Option<usize> {
+        return match *self {
+            Op::Add(_, _, Operand::Position(d)) | Op::Mul(_, _, Operand::Position(d)) => Some(d),
+            Op::Lt(_, _, Operand::Position(d)) | Op::Eq(_, _, Operand::Position(d)) => Some(d),
+            Op::Input(Operand::Position(d)) => Some(d),
+            _ => None,
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Cond {
+    NonZero(String),
+    Zero(String),
+    Lt(String, String),
+    Ge(String, String),
+    Eq(String, String),
+    Ne(String, String),
+    Always,
+}
+
+impl Cond {
+
+    fn negate(&self) -> Cond {
+        return match self.clone() {
+            Cond::NonZero(a) => Cond::Zero(a),
+            Cond::Zero(a) => Cond::NonZero(a),
+            Cond::Lt(a, b) => Cond::Ge(a, b),
+            Cond::Ge(a, b) => Cond::Lt(a, b),
+            Cond::Eq(a, b) => Cond::Ne(a, b),
+            Cond::Ne(a, b) => Cond::Eq(a, b),
+            Cond::Always => panic!("unconditional jump has no negation"),
+        };
+    }
+
+    fn render(&self) -> String {
+        return match self {
+            Cond::NonZero(a) => format!("{} != 0", a),
+            Cond::Zero(a) => format!("{} == 0", a),
+            Cond::Lt(a, b) => format!("{} < {}", a, b),
+            Cond::Ge(a, b) => format!("{} >= {}", a, b),
+            Cond::Eq(a, b) => format!("{} == {}", a, b),
+            Cond::Ne(a, b) => format!("{} != {}", a, b),
+            Cond::Always => String::from("true"),
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Exit {
+    Fall(usize),
+    Goto(usize),
+    Branch { cond: Cond, target: usize, fall: usize },
+    Indirect { cond: Cond, target: String },
+    Halt,
+    Unknown(usize),
+}
+
+#[derive(Debug)]
+struct Block {
+    start: usize,
+    end: usize,
+    stmts: Vec<String>,
+    exit: Exit,
+}
+
+struct Decompiler {
+    code: BTreeMap<usize, (Op, usize)>,
+    reads: HashMap<usize, usize>,
+    cond_reads: HashMap<usize, usize>,
+    writes: HashSet<usize>,
+    blocks: BTreeMap<usize, Block>,
+}
+
+// Loop context used while emitting a loop body: jumps to `header` become
+// `continue`, jumps to `exit` become `break`, and the latch's own back edge is
+// expressed by the enclosing loop construct. `depth` is the indentation of the
+// statements being emitted.
+#[derive(Clone, Copy)]
+struct Context {
+    header: Option<usize>,
+    exit: Option<usize>,
+    latch: Option<usize>,
+    depth: usize,
+}
+
+impl Context {
+
+    fn nested(self) -> Context {
+        return Context { depth: self.depth + 1, ..self };
+    }
+}
+
+// The emitted lines with their indentation, and a label marker at the start
+// of every block; only the labels of blocks some `goto` targets are rendered.
+struct Listing {
+    lines: Vec<(usize, Option<usize>, String)>,
+    gotos: HashSet<usize>,
+}
+
+impl Listing {
+
+    fn push(&mut self, depth: usize, line: String) {
+        self.lines.push((depth, None, line));
+    }
+
+    fn label(&mut self, depth: usize, address: usize) {
+        self.lines.push((depth, Some(address), String::new()));
+    }
+}
+
+impl Decompiler {
+
+    fn new(program: &[isize]) -> Decompiler {
+
+        let mut code = BTreeMap::new();
+        let mut pending = vec![0];
+
+        while let Some(start) = pending.pop() {
+            let mut address = start;
+            while !code.contains_key(&address) {
+                let instruction = match program.get(address).map(|w| Instruction::decode(*w)) {
+                    Some(Ok(i)) => i,
+                    _ => break,
+                };
+                let (op, len) = match Op::lift(&instruction, program, address) {
+                    Some(op) => (op, instruction.len()),
+                    None => break,
+                };
+                code.insert(address, (op, len));
+                if let Op::Jnz(_, Operand::Immediate(t)) | Op::Jz(_, Operand::Immediate(t)) = op {
+                    if t >= 0 {
+                        pending.push(t as usize);
+                    }
+                }
+                match op {
+                    Op::Halt => break,
+                    Op::Jnz(c, _) | Op::Jz(c, _) if Decompiler::constant_jump(&op, &c) == Some(true) => break,
+                    _ => {},
+                }
+                address += len;
+            }
+        }
+
+        let mut reads = HashMap::new();
+        let mut cond_reads = HashMap::new();
+        let mut writes = HashSet::new();
+        for (op, _) in code.values() {
+            for operand in op.reads() {
+                if let Operand::Position(a) = operand {
+                    *reads.entry(a).or_insert(0) += 1;
+                }
+            }
+            match op {
+                Op::Jnz(Operand::Position(a), _) | Op::Jz(Operand::Position(a), _) => {
+                    *cond_reads.entry(*a).or_insert(0) += 1;
+                },
+                _ => {},
+            }
+            if let Some(d) = op.writes() {
+                writes.insert(d);
+            }
+        }
+
+        let mut decompiler = Decompiler { code, reads, cond_reads, writes, blocks: BTreeMap::new() };
+        decompiler.build_blocks();
+        return decompiler;
+    }
+
+    // Whether a jump whose condition is an immediate is always (true) or never
+    // (false) taken; `None` when it depends on memory.
+    fn constant_jump(op: &Op, cond: &Operand) -> Option<bool> {
+        return match (op, cond) {
+            (Op::Jnz(..), Operand::Immediate(v)) => Some(*v != 0),
+            (Op::Jz(..), Operand::Immediate(v)) => Some(*v == 0),
+            _ => None,
+        };
+    }
+
+    fn is_code(&self, address: usize) -> bool {
+        return self.code.range(..=address).next_back()
+            .map(|(start, (_, len))| address < start + len)
+            .unwrap_or(false);
+    }
+
+    fn name(&self, address: usize) -> String {
+        return match self.is_code(address) {
+            true => format!("mem[{}]", address),
+            false => format!("v{}", address),
+        };
+    }
+
+    fn operand(&self, operand: &Operand) -> String {
+        return match operand {
+            Operand::Position(a) => self.name(*a),
+            Operand::Immediate(v) => v.to_string(),
+            Operand::Relative(0) => String::from("mem[rb]"),
+            Operand::Relative(v) if *v < 0 => format!("mem[rb - {}]", -v),
+            Operand::Relative(v) => format!("mem[rb + {}]", v),
+        };
+    }
+
+    // Whether an immediate jump target starts a decoded instruction. Anything
+    // else, a negative address or one inside another instruction, has no label
+    // to jump to and is left as a raw `goto *`.
+    fn is_target(&self, target: isize) -> bool {
+        return target >= 0 && self.code.contains_key(&(target as usize));
+    }
+
+    // The blocks starting in `[from, to)`, or none when `from` is already past
+    // `to`, which overlapping instructions can cause.
+    fn blocks_in(&self, from: usize, to: usize) -> btree_map::Range<'_, usize, Block> {
+        return self.blocks.range(from..to.max(from));
+    }
+
+    fn leaders(&self) -> BTreeSet<usize> {
+
+        let mut leaders = BTreeSet::new();
+        leaders.insert(0);
+
+        for (address, (op, len)) in self.code.iter() {
+            match op {
+                Op::Jnz(_, t) | Op::Jz(_, t) => {
+                    if let Operand::Immediate(t) = t {
+                        if self.is_target(*t) {
+                            leaders.insert(*t as usize);
+                        }
+                    }
+                    leaders.insert(address + len);
+                },
+                Op::Halt => { leaders.insert(address + len); },
+                _ => {},
+            }
+            if !self.code.contains_key(&(address + len)) {
+                leaders.insert(address + len);
+            }
+        }
+
+        return leaders.into_iter().filter(|a| self.code.contains_key(a)).collect();
+    }
+
+    fn build_blocks(&mut self) {
+
+        let leaders = self.leaders();
+
+        for start in leaders.iter().cloned() {
+
+            let mut stmts: Vec<(String, Option<(Operand, Cond)>)> = Vec::new();
+            let mut address = start;
+
+            let exit = loop {
+
+                let (op, len) = self.code[&address];
+                let next = address + len;
+
+                let exit = match op {
+                    Op::Add(a, b, d) => {
+                        let stmt = match (a, b) {
+                            (Operand::Immediate(x), Operand::Immediate(y)) =>
+                                format!("{} = {};", self.operand(&d), x + y),
+                            (x, Operand::Immediate(0)) | (Operand::Immediate(0), x) =>
+                                format!("{} = {};", self.operand(&d), self.operand(&x)),
+                            (x, Operand::Immediate(y)) if y < 0 =>
+                                format!("{} = {} - {};", self.operand(&d), self.operand(&x), -y),
+                            _ => format!("{} = {} + {};", self.operand(&d), self.operand(&a), self.operand(&b)),
+                        };
+                        stmts.push((stmt, None));
+                        None
+                    },
+                    Op::Mul(a, b, d) => {
+                        let stmt = match (a, b) {
+                            (Operand::Immediate(x), Operand::Immediate(y)) =>
+                                format!("{} = {};", self.operand(&d), x * y),
+                            (_, Operand::Immediate(0)) | (Operand::Immediate(0), _) =>
+                                format!("{} = 0;", self.operand(&d)),
+                            (x, Operand::Immediate(1)) | (Operand::Immediate(1), x) =>
+                                format!("{} = {};", self.operand(&d), self.operand(&x)),
+                            (x, Operand::Immediate(-1)) | (Operand::Immediate(-1), x) =>
+                                format!("{} = -{};", self.operand(&d), self.operand(&x)),
+                            _ => format!("{} = {} * {};", self.operand(&d), self.operand(&a), self.operand(&b)),
+                        };
+                        stmts.push((stmt, None));
+                        None
+                    },
+                    Op::Lt(a, b, d) => {
+                        let (x, y) = (self.operand(&a), self.operand(&b));
+                        let stmt = format!("{} = {} < {};", self.operand(&d), x, y);
+                        let aliased = a == d || b == d;
+                        stmts.push((stmt, match aliased {
+                            true => None,
+                            false => Some((d, Cond::Lt(x, y))),
+                        }));
+                        None
+                    },
+                    Op::Eq(a, b, d) => {
+                        let (x, y) = (self.operand(&a), self.operand(&b));
+                        let stmt = format!("{} = {} == {};", self.operand(&d), x, y);
+                        let aliased = a == d || b == d;
+                        stmts.push((stmt, match aliased {
+                            true => None,
+                            false => Some((d, Cond::Eq(x, y))),
+                        }));
+                        None
+                    },
+                    Op::Input(d) => {
+                        stmts.push((format!("{} = input();", self.operand(&d)), None));
+                        None
+                    },
+                    Op::Output(a) => {
+                        stmts.push((format!("output({});", self.operand(&a)), None));
+                        None
+                    },
+                    Op::Arb(a) => {
+                        let stmt = match a {
+                            Operand::Immediate(v) if v < 0 => format!("rb -= {};", -v),
+                            _ => format!("rb += {};", self.operand(&a)),
+                        };
+                        stmts.push((stmt, None));
+                        None
+                    },
+                    Op::Halt => Some(Exit::Halt),
+                    Op::Jnz(c, t) | Op::Jz(c, t) => {
+                        let constant = Decompiler::constant_jump(&op, &c);
+                        let cond = match constant {
+                            Some(_) => Cond::Always,
+                            None => self.fold_condition(&op, &c, &mut stmts),
+                        };
+                        let exit = match (constant, t) {
+                            (Some(false), _) => Exit::Fall(next),
+                            (Some(true), Operand::Immediate(t)) if self.is_target(t) => Exit::Goto(t as usize),
+                            (None, Operand::Immediate(t)) if self.is_target(t) =>
+                                Exit::Branch { cond, target: t as usize, fall: next },
+                            (_, _) => Exit::Indirect { cond, target: self.operand(&t) },
+                        };
+                        Some(exit)
+                    },
+                };
+
+                if let Some(exit) = exit {
+                    break exit;
+                }
+                if leaders.contains(&next) {
+                    break Exit::Fall(next);
+                }
+                if !self.code.contains_key(&next) {
+                    break Exit::Unknown(next);
+                }
+                address = next;
+            };
+
+            let end = match &exit {
+                Exit::Fall(next) => *next,
+                _ => address + self.code[&address].1,
+            };
+
+            let stmts = stmts.into_iter().map(|(s, _)| s).collect();
+            self.blocks.insert(start, Block { start, end, stmts, exit });
+        }
+    }
+
+    // Recognizes `lt`/`eq` into a cell followed by a `jnz`/`jz` on that cell,
+    // and lifts the comparison into the jump condition. The assignment is
+    // dropped when the cell is never read other than as a jump condition.
+    fn fold_condition(&self, op: &Op, cond: &Operand, stmts: &mut Vec<(String, Option<(Operand, Cond)>)>) -> Cond {
+
+        let taken_on_nonzero = matches!(op, Op::Jnz(..));
+
+        if let Some((_, Some((d, compare)))) = stmts.last() {
+            if cond == d {
+                let compare = compare.clone();
+                // relative cells could be read through any base, so they stay
+                let unread = match cond {
+                    Operand::Position(a) => self.reads.get(a) == self.cond_reads.get(a),
+                    _ => false,
+                };
+                if unread {
+                    stmts.pop();
+                }
+                return match taken_on_nonzero {
+                    true => compare,
+                    false => compare.negate(),
+                };
+            }
+        }
+
+        let value = self.operand(cond);
+        return match taken_on_nonzero {
+            true => Cond::NonZero(value),
+            false => Cond::Zero(value),
+        };
+    }
+
+    // The block with the highest address in `[header, to)` that jumps back to
+    // `header`, if any.
+    fn latch(&self, header: usize, to: usize) -> Option<&Block> {
+        return self.blocks_in(header, to)
+            .map(|(_, b)| b)
+            .rfind(|b| b.end <= to && match b.exit {
+                Exit::Goto(t) => t == header,
+                Exit::Branch { target, .. } => target == header,
+                _ => false,
+            });
+    }
+
+    fn jump(&self, target: usize, ctx: &Context, out: &mut Listing) -> String {
+        if ctx.header == Some(target) {
+            return String::from("continue;");
+        }
+        if ctx.exit == Some(target) {
+            return String::from("break;");
+        }
+        out.gotos.insert(target);
+        return format!("goto L{};", target);
+    }
+
+    fn region(&self, from: usize, to: usize, follow: usize, ctx: Context, skip_loop: bool, out: &mut Listing) {
+
+        let depth = ctx.depth;
+        let mut address = from;
+        let mut first = true;
+
+        while address < to {
+
+            let block = match self.blocks_in(address, to).next() {
+                Some((_, b)) => b,
+                None => break,
+            };
+
+            out.label(depth, block.start);
+
+            if !(first && skip_loop) {
+                if let Some(latch) = self.latch(block.start, to) {
+                    address = self.emit_loop(block, latch, depth, out);
+                    first = false;
+                    continue;
+                }
+            }
+            first = false;
+
+            for stmt in block.stmts.iter() {
+                out.push(depth, stmt.clone());
+            }
+
+            if ctx.latch == Some(block.start) {
+                address = block.end;
+                continue;
+            }
+
+            address = match &block.exit {
+                Exit::Fall(next) => *next,
+                Exit::Halt => {
+                    out.push(depth, String::from("halt;"));
+                    block.end
+                },
+                Exit::Unknown(next) => {
+                    let stmt = match self.writes.contains(next) {
+                        true => format!("exec mem[{}]; // patched at runtime", next),
+                        false => format!("data @ {};", next),
+                    };
+                    out.push(depth, stmt);
+                    block.end
+                },
+                Exit::Goto(t) => {
+                    let next = self.blocks_in(block.end, to).next().map(|(a, _)| *a);
+                    if !(*t == follow && block.end == to) && next != Some(*t) {
+                        let jump = self.jump(*t, &ctx, out);
+                        out.push(depth, jump);
+                    }
+                    block.end
+                },
+                Exit::Indirect { cond, target, .. } => {
+                    let stmt = match cond {
+                        Cond::Always => format!("goto *{};", target),
+                        c => format!("if ({}) goto *{};", c.render(), target),
+                    };
+                    out.push(depth, stmt);
+                    block.end
+                },
+                Exit::Branch { cond, target, fall } => {
+                    let target = *target;
+                    if target > block.start && target <= to && *fall < target {
+                        let else_end = self.blocks_in(*fall, target).next_back()
+                            .and_then(|(_, b)| match b.exit {
+                                Exit::Goto(j) if j > target && j <= to && b.end <= target => Some(j),
+                                _ => None,
+                            })
+                            .filter(|j| Some(*j) != ctx.exit);
+                        out.push(depth, format!("if ({}) {{", cond.negate().render()));
+                        match else_end {
+                            Some(j) => {
+                                self.region(*fall, target, j, ctx.nested(), false, out);
+                                out.push(depth, String::from("} else {"));
+                                self.region(target, j, j, ctx.nested(), false, out);
+                                out.push(depth, String::from("}"));
+                                j
+                            },
+                            None => {
+                                self.region(*fall, target, target, ctx.nested(), false, out);
+                                out.push(depth, String::from("}"));
+                                target
+                            },
+                        }
+                    } else {
+                        let jump = self.jump(target, &ctx, out);
+                        out.push(depth, format!("if ({}) {}", cond.render(), jump));
+                        block.end
+                    }
+                },
+            };
+        }
+    }
+
+    fn emit_loop(&self, header: &Block, latch: &Block, depth: usize, out: &mut Listing) -> usize {
+
+        let end = latch.end;
+        let ctx = Context { header: Some(header.start), exit: Some(end), latch: Some(latch.start), depth: depth + 1 };
+
+        match &latch.exit {
+            Exit::Branch { cond, .. } => {
+                out.push(depth, String::from("do {"));
+                self.region(header.start, end, end, ctx, true, out);
+                // the latch's statements were emitted above, only its back edge remains
+                out.push(depth, format!("}} while ({});", cond.render()));
+            },
+            _ => {
+                let guard = match &header.exit {
+                    Exit::Branch { cond, target, fall } if *target == end && header.stmts.is_empty()
+                        && header.start != latch.start => Some((cond.negate(), *fall)),
+                    _ => None,
+                };
+                match guard {
+                    Some((cond, fall)) => {
+                        out.push(depth, format!("while ({}) {{", cond.render()));
+                        self.region(fall, end, end, ctx, false, out);
+                    },
+                    None => {
+                        out.push(depth, String::from("loop {"));
+                        self.region(header.start, end, end, ctx, true, out);
+                    },
+                }
+                out.push(depth, String::from("}"));
+            },
+        }
+
+        return end;
+    }
+
+    fn render(&self) -> String {
+
+        let mut out = Listing { lines: Vec::new(), gotos: HashSet::new() };
+        let ctx = Context { header: None, exit: None, latch: None, depth: 0 };
+        let end = self.blocks.values().map(|b| b.end).max().unwrap_or(0);
+
+        self.region(0, end, end, ctx, false, &mut out);
+
+        let mut text = String::new();
+        for (depth, label, line) in out.lines {
+            match label {
+                Some(address) if out.gotos.contains(&address) => {
+                    text.push_str(&format!("{}L{}:\n", "    ".repeat(depth), address));
+                },
+                Some(_) => {},
+                None => {
+                    text.push_str(&format!("{}{}\n", "    ".repeat(depth), line));
+                },
+            }
+        }
+        return text;
+    }
+}
+
+pub fn decompile(program: &[isize]) -> String {
+    return Decompiler::new(program).render();
+}
+
+// A linear listing: every address that decodes as an instruction is shown as
+// one, anything else as a raw data word.
+pub fn disassemble(program: &[isize]) -> String {
+
+    let mut text = String::new();
+    let mut address = 0;
+
+    while address < program.len() {
+
+        let instruction = match Instruction::decode(program[address]) {
+            Ok(i) if address + i.len() <= program.len() => i,
+            _ => {
+                text.push_str(&format!("{:>6}: .word {}\n", address, program[address]));
+                address += 1;
+                continue;
+            },
+        };
+
+        let operands = instruction.params().iter().enumerate()
+            .map(|(i, param)| {
+                let value = program[address + i + 1];
+                return match param {
+                    Param::Read(Mode::Immediate) => value.to_string(),
+                    Param::Read(Mode::Position) | Param::Write(Mode::Position) => format!("[{}]", value),
+                    Param::Read(Mode::Relative) | Param::Write(Mode::Relative) => format!("[rb{:+}]", value),
+                    Param::Write(Mode::Immediate) => unreachable!(),
+                };
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        text.push_str(&format!("{:>6}: {} {}\n", address, instruction.opcode.mnemonic(), operands));
+        address += instruction.len();
+    }
+
+    return text;
+}
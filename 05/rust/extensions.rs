@@ -0,0 +1,42 @@
+use crate::intcode::Flow;
+use crate::intcode::IntcodeProcessor;
+use crate::intcode::OpcodeRegistry;
+use crate::intcode::ParameterKind;
+
+pub const DEBUG_PRINT: isize = 50;
+pub const ASSERT_EQ: isize = 51;
+pub const HOST_CALL: isize = 52;
+
+fn debug_print(processor: &mut IntcodeProcessor, args: &[isize]) -> Flow {
+    eprintln!("debug @ {}: {}", processor.program_counter(), args[0]);
+    return Flow::Advance;
+}
+
+fn assert_eq(processor: &mut IntcodeProcessor, args: &[isize]) -> Flow {
+    if args[0] != args[1] {
+        panic!("assertion failed @ {}: {} != {}", processor.program_counter(), args[0], args[1]);
+    }
+    return Flow::Advance;
+}
+
+// Host call 0 reports the number of words currently in memory, which lets a
+// program size its own scratch space.
+fn host_call(processor: &mut IntcodeProcessor, args: &[isize]) -> Flow {
+    let result = match args[0] {
+        0 => processor.memory().len() as isize,
+        x => panic!("unknown host call {} @ {}", x, processor.program_counter()),
+    };
+    processor.store(args[1] as usize, result);
+    return Flow::Advance;
+}
+
+pub fn debug_extensions() -> OpcodeRegistry {
+
+    let mut registry = OpcodeRegistry::new();
+
+    registry.register(DEBUG_PRINT, &[ParameterKind::Value], debug_print).unwrap();
+    registry.register(ASSERT_EQ, &[ParameterKind::Value, ParameterKind::Value], assert_eq).unwrap();
+    registry.register(HOST_CALL, &[ParameterKind::Value, ParameterKind::Reference], host_call).unwrap();
+
+    return registry;
+}
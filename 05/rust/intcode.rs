@@ -0,0 +1,479 @@
+// The one Intcode processor. The other Intcode days build on their own, so
+// they get copies of this file and instruction.rs made by ./sync-intcode;
+// change the originals here and run it. Each day only uses part of them.
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+
+use crate::instruction::Access;
+use crate::instruction::Instruction;
+use crate::instruction::MAX_PARAMS;
+use crate::instruction::Mode;
+use crate::instruction::Opcode;
+use crate::instruction::Param;
+
+#[derive(Debug)]
+pub struct MemoryAccessError {
+    address: isize,
+}
+
+impl MemoryAccessError {
+    fn new(address: &isize) -> MemoryAccessError {
+        return MemoryAccessError { address: *address };
+    }
+}
+
+impl fmt::Display for MemoryAccessError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "Invalid memory access @ {}", self.address);
+    }
+}
+
+impl Error for MemoryAccessError {}
+
+#[derive(Debug)]
+pub struct InvalidInstructionError {
+    instruction: isize,
+    address: usize,
+    reason: String,
+}
+
+impl InvalidInstructionError {
+    fn new (instruction: &isize, address: &usize, reason: &str) -> InvalidInstructionError {
+        return InvalidInstructionError { instruction: *instruction, address: *address, reason: reason.to_string() };
+    }
+}
+
+impl fmt::Display for InvalidInstructionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "Invalid instruction {} @ {}: {}", self.instruction, self.address, self.reason);
+    }
+}
+
+impl Error for InvalidInstructionError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParameterKind {
+    Value,
+    Reference,
+}
+
+impl ParameterKind {
+    pub fn access(&self) -> Access {
+        return match self {
+            ParameterKind::Value => Access::Read,
+            ParameterKind::Reference => Access::Write,
+        };
+    }
+}
+
+// What a handler did with the program counter: `Advance` leaves it for the
+// processor to move past the instruction, `Jumped` means the handler set it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    Advance,
+    Jumped,
+}
+
+// Called with one argument per declared parameter: the resolved value for
+// `Value` parameters, the target address for `Reference` parameters.
+pub type Handler = fn(&mut IntcodeProcessor, &[isize]) -> Flow;
+
+#[derive(Clone)]
+pub struct CustomOpcode {
+    pub parameters: Vec<ParameterKind>,
+    pub handler: Handler,
+}
+
+#[derive(Clone)]
+pub struct OpcodeRegistry {
+    opcodes: HashMap<isize, CustomOpcode>,
+}
+
+impl OpcodeRegistry {
+
+    pub fn new() -> OpcodeRegistry {
+        return OpcodeRegistry { opcodes: HashMap::new() };
+    }
+
+    pub fn register(&mut self, opcode: isize, parameters: &[ParameterKind], handler: Handler) -> Result<(), RegistrationError> {
+
+        if opcode <= 0 || opcode >= 100 {
+            return Err(RegistrationError::new(&opcode, "opcode must be between 1 and 99"));
+        }
+
+        if Opcode::from_code(opcode).is_some() {
+            return Err(RegistrationError::new(&opcode, "opcode is part of the standard instruction set"));
+        }
+
+        if parameters.len() > MAX_PARAMS {
+            return Err(RegistrationError::new(&opcode, &format!("at most {} parameters are supported", MAX_PARAMS)));
+        }
+
+        if self.opcodes.contains_key(&opcode) {
+            return Err(RegistrationError::new(&opcode, "opcode is already registered"));
+        }
+
+        self.opcodes.insert(opcode, CustomOpcode { parameters: parameters.to_vec(), handler });
+
+        return Ok(());
+    }
+
+    pub fn get(&self, opcode: &isize) -> Option<&CustomOpcode> {
+        return self.opcodes.get(opcode);
+    }
+}
+
+#[derive(Debug)]
+pub struct RegistrationError {
+    opcode: isize,
+    reason: String,
+}
+
+impl RegistrationError {
+    fn new(opcode: &isize, reason: &str) -> RegistrationError {
+        return RegistrationError { opcode: *opcode, reason: reason.to_string() };
+    }
+}
+
+impl fmt::Display for RegistrationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "Cannot register opcode {}: {}", self.opcode, self.reason);
+    }
+}
+
+impl Error for RegistrationError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Running,
+    AwaitingInput,
+    Halted,
+}
+
+// Input and output are queues: `run` returns as soon as the program asks for
+// input that hasn't been provided yet, and picks up from the same instruction
+// on the next call. `run_with` hooks the queues up to callbacks instead.
+#[derive(Clone)]
+pub struct IntcodeProcessor {
+    program_counter: usize,
+    relative_base: isize,
+    memory: HashMap<usize, isize>,
+    inputs: VecDeque<isize>,
+    outputs: VecDeque<isize>,
+    state: State,
+    extensions: OpcodeRegistry,
+    instructions: usize,
+}
+
+impl IntcodeProcessor {
+
+    pub fn new(program: &[isize]) -> IntcodeProcessor {
+
+        let mut computer = IntcodeProcessor {
+            program_counter: 0,
+            relative_base: 0,
+            memory: HashMap::new(),
+            inputs: VecDeque::new(),
+            outputs: VecDeque::new(),
+            state: State::Running,
+            extensions: OpcodeRegistry::new(),
+            instructions: 0,
+        };
+
+        program.iter().enumerate()
+            .for_each(|(addr,word)| {
+                computer.store(addr, *word);
+            });
+
+        return computer;
+    }
+
+    pub fn extend(&mut self, extensions: &OpcodeRegistry) {
+        self.extensions = extensions.clone();
+    }
+
+    pub fn store(&mut self, address: usize, word: isize) {
+        self.memory.insert(address, word);
+    }
+
+    // Memory past the program reads as 0.
+    pub fn fetch(&self, address: &usize) -> isize {
+        return self.memory.get(address).copied().unwrap_or(0);
+    }
+
+    pub fn memory(&self) -> &HashMap<usize, isize> {
+        return &self.memory;
+    }
+
+    pub fn program_counter(&self) -> usize {
+        return self.program_counter;
+    }
+
+    pub fn jump(&mut self, address: usize) {
+        self.program_counter = address;
+    }
+
+    pub fn relative_base(&self) -> isize {
+        return self.relative_base;
+    }
+
+    // The number of instructions executed so far.
+    pub fn instructions(&self) -> usize {
+        return self.instructions;
+    }
+
+    pub fn state(&self) -> State {
+        return self.state;
+    }
+
+    pub fn push_input(&mut self, value: isize) {
+        self.inputs.push_back(value);
+        if self.state == State::AwaitingInput {
+            self.state = State::Running;
+        }
+    }
+
+    pub fn pop_output(&mut self) -> Option<isize> {
+        return self.outputs.pop_front();
+    }
+
+    pub fn run(&mut self) -> State {
+        while self.step() == State::Running {}
+        return self.state;
+    }
+
+    // Runs until the program halts, asking `read` whenever it wants input
+    // that hasn't been queued and handing each output to `write` as soon as
+    // it's produced.
+    pub fn run_with<R: FnMut() -> isize, W: FnMut(isize)>(&mut self, mut read: R, mut write: W) {
+        loop {
+            let state = self.step();
+            while let Some(value) = self.pop_output() {
+                write(value);
+            }
+            match state {
+                State::Running => {},
+                State::AwaitingInput => self.push_input(read()),
+                State::Halted => return,
+            }
+        }
+    }
+
+    // Executes a single instruction. The program counter is left on the
+    // halt instruction, or on an input instruction that is still waiting.
+    pub fn step(&mut self) -> State {
+
+        let instruction = self.decode();
+
+        self.state = State::Running;
+
+        match instruction.opcode {
+            Opcode::Add => self.add(&instruction),
+            Opcode::Mul => self.mul(&instruction),
+            Opcode::Input => self.input(&instruction),
+            Opcode::Output => self.output(&instruction),
+            Opcode::JumpIfTrue => self.jnz(&instruction),
+            Opcode::JumpIfFalse => self.jz(&instruction),
+            Opcode::LessThan => self.lt(&instruction),
+            Opcode::Equals => self.eq(&instruction),
+            Opcode::AdjustBase => self.adjust_base(&instruction),
+            Opcode::Halt => self.state = State::Halted,
+            Opcode::Custom(x) => self.custom(x, &instruction),
+        }
+
+        if self.state == State::Running {
+            self.instructions += 1;
+        }
+
+        return self.state;
+    }
+
+    // Extension instructions are only looked up for opcodes outside the
+    // standard set, so they cost nothing for ordinary programs.
+    fn decode(&self) -> Instruction {
+
+        let word = self.instruction();
+
+        let custom = match Opcode::from_code(word % 100) {
+            Some(_) => None,
+            None => self.extensions.get(&(word % 100)),
+        };
+
+        let decoded = match custom {
+            Some(op) => {
+                let parameters = op.parameters.iter().map(|p| p.access()).collect::<Vec<_>>();
+                Instruction::decode_custom(word, &parameters)
+            },
+            None => Instruction::decode(word),
+        };
+
+        return match decoded {
+            Ok(instruction) => instruction,
+            Err(e) => panic!("{}", InvalidInstructionError::new(&word, &self.program_counter, &e.to_string())),
+        };
+    }
+
+    fn custom(&mut self, opcode: isize, instruction: &Instruction) {
+
+        let handler = self.extensions.get(&opcode).unwrap().handler;
+
+        let args = instruction.params().iter().enumerate()
+            .map(|(i, param)| match param {
+                Param::Read(_) => self.val_arg(instruction, i + 1),
+                Param::Write(_) => self.ref_arg(instruction, i + 1) as isize,
+            })
+            .collect::<Vec<_>>();
+
+        if handler(self, &args) == Flow::Advance {
+            self.program_counter += instruction.len();
+        }
+    }
+
+    fn instruction(&self) -> isize {
+        return self.fetch(&self.program_counter);
+    }
+
+    fn add(&mut self, instruction: &Instruction) {
+
+        let arg1 = self.val_arg(instruction, 1);
+        let arg2 = self.val_arg(instruction, 2);
+        let dst = self.ref_arg(instruction, 3);
+
+        self.store(dst, arg1 + arg2);
+
+        self.program_counter += 4;
+    }
+
+    fn mul(&mut self, instruction: &Instruction) {
+
+        let arg1 = self.val_arg(instruction, 1);
+        let arg2 = self.val_arg(instruction, 2);
+        let dst = self.ref_arg(instruction, 3);
+
+        self.store(dst, arg1 * arg2);
+
+        self.program_counter += 4;
+    }
+
+    fn input(&mut self, instruction: &Instruction) {
+
+        let value = match self.inputs.pop_front() {
+            Some(v) => v,
+            None => {
+                self.state = State::AwaitingInput;
+                return;
+            },
+        };
+
+        let dst = self.ref_arg(instruction, 1);
+
+        self.store(dst, value);
+
+        self.program_counter += 2;
+    }
+
+    fn output(&mut self, instruction: &Instruction) {
+
+        let arg = self.val_arg(instruction, 1);
+
+        self.outputs.push_back(arg);
+
+        self.program_counter += 2;
+    }
+
+    fn jnz(&mut self, instruction: &Instruction) {
+
+        let arg1 = self.val_arg(instruction, 1);
+        let arg2 = self.val_arg(instruction, 2);
+
+        self.program_counter = match arg1 {
+            0 => self.program_counter + 3,
+            _ => self.address(arg2),
+        }
+    }
+
+    fn jz(&mut self, instruction: &Instruction) {
+
+        let arg1 = self.val_arg(instruction, 1);
+        let arg2 = self.val_arg(instruction, 2);
+
+        self.program_counter = match arg1 {
+            0 => self.address(arg2),
+            _ => self.program_counter + 3,
+        }
+    }
+
+    fn lt(&mut self, instruction: &Instruction) {
+
+        let arg1 = self.val_arg(instruction, 1);
+        let arg2 = self.val_arg(instruction, 2);
+        let dst = self.ref_arg(instruction, 3);
+
+        self.store(dst, match arg1 < arg2 {
+            true => 1,
+            false => 0,
+        });
+
+        self.program_counter += 4;
+    }
+
+    fn eq(&mut self, instruction: &Instruction) {
+
+        let arg1 = self.val_arg(instruction, 1);
+        let arg2 = self.val_arg(instruction, 2);
+        let dst = self.ref_arg(instruction, 3);
+
+        self.store(dst, match arg1 == arg2 {
+            true => 1,
+            false => 0,
+        });
+
+        self.program_counter += 4;
+    }
+
+    fn adjust_base(&mut self, instruction: &Instruction) {
+
+        let arg1 = self.val_arg(instruction, 1);
+
+        self.relative_base += arg1;
+
+        self.program_counter += 2;
+    }
+
+    fn address(&self, value: isize) -> usize {
+        if value < 0 {
+            panic!("{} (pc {})", MemoryAccessError::new(&value), self.program_counter);
+        }
+        return value as usize;
+    }
+
+    fn val_arg(&self, instruction: &Instruction, position: usize) -> isize {
+        let value = self.fetch(&(self.program_counter + position));
+        return match instruction.params()[position - 1] {
+            Param::Read(Mode::Position) => self.fetch(&self.address(value)),
+            Param::Read(Mode::Immediate) => value,
+            Param::Read(Mode::Relative) => self.fetch(&self.address(self.relative_base + value)),
+            Param::Write(_) => panic!("parameter {} is written, not read (@ {})", position, self.program_counter),
+        };
+    }
+
+    fn ref_arg(&self, instruction: &Instruction, position: usize) -> usize {
+        let value = self.fetch(&(self.program_counter + position));
+        return match instruction.params()[position - 1] {
+            Param::Write(Mode::Position) => self.address(value),
+            Param::Write(Mode::Relative) => self.address(self.relative_base + value),
+            _ => panic!("parameter {} is not a write target (@ {})", position, self.program_counter),
+        };
+    }
+}
+
+pub fn read_program(line: &str) -> Vec<isize> {
+    return line.trim()
+        .split(",")
+        .map(|w| w.parse::<isize>().unwrap())
+        .collect();
+}
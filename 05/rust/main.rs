@@ -1,218 +1,21 @@
-use std::collections::HashMap;
+mod ascii;
+mod decompiler;
+mod extensions;
+mod instruction;
+mod intcode;
+mod recorder;
+mod repl;
+mod transpiler;
+
+use std::collections::VecDeque;
+use std::env;
 use std::error::Error;
 use std::fmt;
 use std::io;
 use std::io::BufRead;
 
-#[derive(Debug)]
-struct MemoryAccessError {
-    address: usize,
-}
-
-impl MemoryAccessError {
-    fn new(address: &usize) -> MemoryAccessError { 
-        return MemoryAccessError { address: *address };
-    }
-}
-
-impl fmt::Display for MemoryAccessError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        return write!(f, "Invalid memory access @ {}", self.address);
-    }
-}
-
-impl Error for MemoryAccessError {}
-
-#[derive(Debug)]
-struct InvalidInstructionError {
-    instruction: isize,
-    address: usize,
-}
-
-impl InvalidInstructionError {
-    fn new (instruction: &isize, address: &usize) -> InvalidInstructionError {
-        return InvalidInstructionError { instruction: *instruction, address: *address };
-    }
-}
-
-impl fmt::Display for InvalidInstructionError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        return write!(f, "Invalid instruction {} @ {}", self.instruction, self.address);
-    }
-}
-
-impl Error for InvalidInstructionError {}
-
-struct IntcodeProcessor {
-    program_counter: usize,
-    memory: HashMap<usize, isize>,
-    read: fn() -> isize,
-    write: fn(isize),
-}
-
-impl IntcodeProcessor {
-
-    fn new(read: fn() -> isize, write: fn(isize)) -> IntcodeProcessor {
-        return IntcodeProcessor { 
-            program_counter: 0,
-            memory: HashMap::new(),
-            read,
-            write,
-        };
-    }
-
-    fn store(&mut self, address: usize, word: isize) {
-        self.memory.insert(address, word);
-    }
-
-    fn fetch(&self, address: &usize) -> isize {
-        return self.memory.get(address)
-            .map(|v| *v)
-            .ok_or(MemoryAccessError::new(address))
-            .unwrap();
-    }
-
-    fn run(&mut self) {
-        loop {
-            match self.opcode() {
-                1 => self.add(),
-                2 => self.mul(),
-                3 => self.input(),
-                4 => self.output(),
-                5 => self.jnz(),
-                6 => self.jz(),
-                7 => self.lt(),
-                8 => self.eq(),
-                99 => break,
-                x => return Err(InvalidInstructionError::new(&x, &self.program_counter)).unwrap(),
-            }
-        }
-    }
-
-    fn instruction(&self) -> isize {
-        return self.fetch(&self.program_counter);
-    }
-
-    fn opcode(&self) -> isize {
-        return self.fetch(&self.program_counter) % 100;
-    }
-
-    fn add(&mut self) {
-        
-        let arg1 = self.val_arg(1);
-        let arg2 = self.val_arg(2);
-        let dst = self.ref_arg(3);
-
-        self.store(dst as usize, arg1 + arg2);
-
-        self.program_counter += 4;
-    }
-
-    fn mul(&mut self) {
-        
-        let arg1 = self.val_arg(1);
-        let arg2 = self.val_arg(2);
-        let dst = self.ref_arg(3);
-
-        self.store(dst as usize, arg1 * arg2);
-
-        self.program_counter += 4;
-    }
-
-    fn input(&mut self) {
-
-        let dst = self.ref_arg(1);
-
-        self.store(dst as usize, (self.read)());
-
-        self.program_counter += 2;
-    }
-
-    fn output(&mut self) {
-
-        let arg = self.val_arg(1);
-
-        (self.write)(arg);
-
-        self.program_counter += 2;
-    }
-
-    fn jnz(&mut self) {
-
-        let arg1 = self.val_arg(1);
-        let arg2 = self.val_arg(2);
-
-        self.program_counter = match arg1 {
-            0 => self.program_counter + 3,
-            _ => arg2 as usize,
-        }
-    }
-
-    fn jz(&mut self) {
-
-        let arg1 = self.val_arg(1);
-        let arg2 = self.val_arg(2);
-
-        self.program_counter = match arg1 {
-            0 => arg2 as usize,
-            _ => self.program_counter + 3,
-        }
-    }
-
-    fn lt(&mut self) {
-
-        let arg1 = self.val_arg(1);
-        let arg2 = self.val_arg(2);
-        let dst = self.ref_arg(3);
-
-        self.store(dst as usize, match arg1 < arg2 {
-            true => 1,
-            false => 0,
-        });
-
-        self.program_counter += 4;
-    }
-
-    fn eq(&mut self) {
-
-        let arg1 = self.val_arg(1);
-        let arg2 = self.val_arg(2);
-        let dst = self.ref_arg(3);
-
-        self.store(dst as usize, match arg1 == arg2 {
-            true => 1,
-            false => 0,
-        });
-
-        self.program_counter += 4;
-    }
-
-
-    fn val_arg(&self, position: usize) -> isize {
-        let value = self.fetch(&(self.program_counter + position));
-        let mode = self.arg_mode(position);
-        let arg = match mode {
-            0 => self.fetch(&(value as usize)), // position mode
-            1 => value, // immediate mode
-            x => panic!("invalid parameter mode {} (@ {})", x, self.program_counter),
-        };
-        return arg;
-    }
-
-    fn ref_arg(&self, position: usize) -> isize {
-        return match self.arg_mode(position) {
-            0 => self.fetch(&(self.program_counter + position)), // position mode
-            x => panic!("invalid parameter mode {} for ref arg (@ {})", x, self.program_counter),
-        }
-    }
-
-    fn arg_mode(&self, position: usize) -> usize {
-        let mode_mask = 10_usize.pow(position as u32 + 1);
-        let shifted = self.instruction() as usize / mode_mask;
-        let mode = shifted % 10;
-        return mode;
-    }
-}
+use intcode::IntcodeProcessor;
+use intcode::OpcodeRegistry;
 
 #[derive(Debug)]
 struct InputError {
@@ -235,11 +38,69 @@ impl Error for InputError {}
 
 fn main() {
 
+    if let Some("repl") = env::args().nth(1).as_deref() {
+        repl::run();
+        return;
+    }
+
     let program = io::stdin().lock().lines().next().unwrap().unwrap()
         .split(",")
         .map(|w| w.parse::<isize>().unwrap())
         .collect::<Vec<_>>();
 
+    let args = env::args().collect::<Vec<_>>();
+    let mut extensions = OpcodeRegistry::new();
+    let mut record = None;
+    let mut replay = None;
+
+    match args.get(1).map(|a| a.as_str()) {
+        Some("disassemble") => {
+            print!("{}", decompiler::disassemble(&program));
+            return;
+        },
+        Some("decompile") => {
+            print!("{}", decompiler::decompile(&program));
+            return;
+        },
+        Some("transpile") => {
+            print!("{}", transpiler::transpile(&program));
+            return;
+        },
+        Some("verify") => {
+            let inputs = args[2..].iter()
+                .map(|a| a.parse::<isize>().unwrap())
+                .collect::<Vec<_>>();
+            match transpiler::verify(&program, &inputs) {
+                Ok(outputs) => println!("ok: {:?}", outputs),
+                Err(err) => {
+                    eprintln!("verify failed: {}", err);
+                    std::process::exit(1);
+                },
+            }
+            return;
+        },
+        Some("ascii") => {
+            let script = args[2..].iter()
+                .filter(|a| *a != "-i")
+                .cloned()
+                .collect::<Vec<_>>();
+            let interactive = script.is_empty() || args[2..].iter().any(|a| a == "-i");
+            ascii::run(&program, &script, interactive);
+            return;
+        },
+        Some("debug") => {
+            extensions = extensions::debug_extensions();
+        },
+        Some("record") => {
+            record = Some(args.get(2).expect("usage: record <session file>").clone());
+        },
+        Some("replay") => {
+            let path = args.get(2).expect("usage: replay <session file>");
+            replay = Some(recorder::load(path).unwrap());
+        },
+        _ => {},
+    }
+
     fn input() -> isize {
         return io::stdin().lock().lines().next().unwrap().unwrap()
             .parse::<isize>()
@@ -251,23 +112,32 @@ fn main() {
         println!("{}", val);
     }
 
-    let mut computer = IntcodeProcessor::new(input, output);
+    let mut sessions = vec![];
 
-    program.iter().enumerate()
-        .for_each(|(addr,word)| {
-            computer.store(addr, *word);
-        });
-    
-    println!("part 1:");
-    computer.run();
+    for part in 1..=2 {
 
-    let mut computer = IntcodeProcessor::new(input, output);
+        let mut computer = IntcodeProcessor::new(&program);
+        computer.extend(&extensions);
 
-    program.iter().enumerate()
-        .for_each(|(addr,word)| {
-            computer.store(addr, *word);
-        });
+        let session = replay.as_mut()
+            .map(|recorded| recorded.pop_front().unwrap_or(VecDeque::new()));
 
-    println!("\r\npart 2:");
-    computer.run();
+        match part {
+            1 => println!("part 1:"),
+            _ => println!("\r\npart 2:"),
+        }
+
+        match recorder::run(&mut computer, input, output, record.is_some(), session) {
+            Ok(Some(journal)) => sessions.push(journal),
+            Ok(None) => {},
+            Err(divergence) => {
+                eprintln!("part {}: {}", part, divergence);
+                std::process::exit(1);
+            },
+        }
+    }
+
+    if let Some(path) = record {
+        recorder::save(&path, &sessions).unwrap();
+    }
 }
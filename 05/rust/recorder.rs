@@ -0,0 +1,176 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+
+use crate::intcode::IntcodeProcessor;
+use crate::intcode::State;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Io {
+    Input(isize),
+    Output(isize),
+}
+
+// An input or output together with the number of instructions the processor
+// had executed when it happened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IoEvent {
+    pub count: usize,
+    pub io: Io,
+}
+
+impl fmt::Display for IoEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self.io {
+            Io::Input(v) => write!(f, "{} in {}", self.count, v),
+            Io::Output(v) => write!(f, "{} out {}", self.count, v),
+        };
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    pub count: usize,
+    pub expected: Option<IoEvent>,
+    pub actual: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self.expected {
+            Some(e) => write!(f, "Diverged @ instruction {}: expected `{}`, got {}", self.count, e, self.actual),
+            None => write!(f, "Diverged @ instruction {}: expected end of session, got {}", self.count, self.actual),
+        };
+    }
+}
+
+impl Error for Divergence {}
+
+#[derive(Debug)]
+pub struct SessionParseError {
+    line: usize,
+    data: String,
+}
+
+impl SessionParseError {
+    fn new(line: &usize, data: &str) -> SessionParseError {
+        return SessionParseError { line: *line, data: data.to_string() };
+    }
+}
+
+impl fmt::Display for SessionParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "Invalid session line {}: {}", self.line, self.data);
+    }
+}
+
+impl Error for SessionParseError {}
+
+// A recording holds one session per processor run, separated by `---` lines
+// in the saved file.
+pub fn save(path: &str, sessions: &[Vec<IoEvent>]) -> Result<(), Box<dyn Error>> {
+
+    let text = sessions.iter()
+        .map(|s| s.iter().map(|e| format!("{}\n", e)).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("---\n");
+
+    fs::write(path, text)?;
+
+    return Ok(());
+}
+
+pub fn load(path: &str) -> Result<VecDeque<VecDeque<IoEvent>>, Box<dyn Error>> {
+
+    let text = fs::read_to_string(path)?;
+
+    let mut sessions = VecDeque::from(vec![VecDeque::new()]);
+
+    for (n, line) in text.lines().enumerate() {
+
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if line == "---" {
+            sessions.push_back(VecDeque::new());
+            continue;
+        }
+
+        let parts = line.split_whitespace().collect::<Vec<_>>();
+        let err = || SessionParseError::new(&(n + 1), line);
+
+        let (count, kind, value) = match parts[..] {
+            [c, k, v] => (c.parse::<usize>().map_err(|_| err())?, k, v.parse::<isize>().map_err(|_| err())?),
+            _ => return Err(Box::new(err())),
+        };
+
+        let io = match kind {
+            "in" => Io::Input(value),
+            "out" => Io::Output(value),
+            _ => return Err(Box::new(err())),
+        };
+
+        sessions.back_mut().unwrap().push_back(IoEvent { count, io });
+    }
+
+    return Ok(sessions);
+}
+
+// Runs the processor until it halts, reading input with `read` and passing
+// output to `write`, and returns the session when `record` is set. With a
+// session to replay, inputs come from it instead of `read`, and every input
+// and output is checked against it; the first mismatch stops the run.
+pub fn run(computer: &mut IntcodeProcessor, read: fn() -> isize, write: fn(isize), record: bool,
+           mut replay: Option<VecDeque<IoEvent>>) -> Result<Option<Vec<IoEvent>>, Divergence> {
+
+    let mut journal = match record {
+        true => Some(vec![]),
+        false => None,
+    };
+
+    loop {
+
+        let count = computer.instructions();
+        let state = computer.step();
+
+        while let Some(value) = computer.pop_output() {
+            if let Some(session) = replay.as_mut() {
+                match session.pop_front() {
+                    Some(e) if e.count == count && e.io == Io::Output(value) => {},
+                    expected => return Err(Divergence { count, expected, actual: format!("output {}", value) }),
+                }
+            }
+            if let Some(journal) = journal.as_mut() {
+                journal.push(IoEvent { count, io: Io::Output(value) });
+            }
+            write(value);
+        }
+
+        match state {
+            State::Running => {},
+            State::AwaitingInput => {
+                let value = match replay.as_mut() {
+                    Some(session) => match session.pop_front() {
+                        Some(IoEvent { count: c, io: Io::Input(v) }) if c == count => v,
+                        expected => return Err(Divergence { count, expected, actual: String::from("an input request") }),
+                    },
+                    None => read(),
+                };
+                if let Some(journal) = journal.as_mut() {
+                    journal.push(IoEvent { count, io: Io::Input(value) });
+                }
+                computer.push_input(value);
+            },
+            State::Halted => {
+                if let Some(expected) = replay.as_mut().and_then(|s| s.pop_front()) {
+                    return Err(Divergence { count, expected: Some(expected), actual: String::from("halt") });
+                }
+                return Ok(journal);
+            },
+        }
+    }
+}
@@ -0,0 +1,321 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+use std::panic;
+
+use crate::intcode::IntcodeProcessor;
+use crate::intcode::State;
+use crate::instruction::Instruction;
+use crate::instruction::Opcode;
+
+const HELP: &str = "\
+Type instructions to write them at the program counter and execute them.
+  numeric:   1101,2,3,100
+  mnemonic:  add 2 3 [100]      ([n] is position mode, n is immediate,
+                                [rb+n] and [rb-n] are relative)
+  several:   in [100]; out [100]
+mnemonics: add mul in out jnz jz lt eq arb hlt
+commands:
+  :load <file>        reset and load a comma separated program
+  :reset              clear memory and the program counter
+  :step [n]           execute n instructions (default 1)
+  :run [n]            run until halt, at most n steps (default 1000000)
+  :mem [from] [to]    show memory
+  :regs               show registers
+  :help               show this text
+  :quit               leave the REPL";
+
+// Instructions typed at the prompt are stopped after this many steps, so a
+// jump back into themselves can't hang the session.
+const STEP_LIMIT: usize = 10_000;
+
+// `:run` gives up after this many steps unless given a count, so a program
+// that never halts can't either.
+const RUN_LIMIT: usize = 1_000_000;
+
+// `None` once stdin is closed; the prompt then finds it closed too and ends
+// the session.
+fn input() -> Result<Option<isize>, String> {
+    print!("input> ");
+    io::stdout().flush().unwrap();
+    let line = match io::stdin().lock().lines().next() {
+        Some(line) => line.map_err(|e| e.to_string())?,
+        None => return Ok(None),
+    };
+    return line.trim().parse::<isize>()
+        .map(Some)
+        .map_err(|_| format!("not a number: {}", line.trim()));
+}
+
+fn output(val: isize) {
+    println!("output: {}", val);
+}
+
+fn machine() -> IntcodeProcessor {
+    return IntcodeProcessor::new(&[]);
+}
+
+fn assemble_one(text: &str) -> Result<Vec<isize>, String> {
+
+    let tokens = text.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|t| !t.is_empty())
+        .collect::<Vec<_>>();
+
+    if tokens.is_empty() {
+        return Ok(vec![]);
+    }
+
+    if tokens[0].parse::<isize>().is_ok() {
+        return tokens.iter()
+            .map(|t| t.parse::<isize>().map_err(|_| format!("not a number: {}", t)))
+            .collect();
+    }
+
+    let opcode = Opcode::from_mnemonic(tokens[0])
+        .ok_or(format!("unknown mnemonic: {}", tokens[0]))?;
+    let arity = opcode.parameters().len();
+
+    if tokens.len() - 1 != arity {
+        return Err(format!("{} takes {} operands, got {}", tokens[0], arity, tokens.len() - 1));
+    }
+
+    let mut instruction = opcode.code();
+    let mut words = vec![];
+
+    for (i, token) in tokens[1..].iter().enumerate() {
+        let (mode, value) = match token.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+            Some(address) => match address.strip_prefix("rb") {
+                Some("") => (2, "0"),
+                Some(offset) => (2, offset),
+                None => (0, address),
+            },
+            None => (1, *token),
+        };
+        let value = value.parse::<isize>().map_err(|_| format!("bad operand: {}", token))?;
+        instruction += mode * 10_isize.pow(i as u32 + 2);
+        words.push(value);
+    }
+
+    // the decoder rejects immediate write targets with a position in the message
+    Instruction::decode(instruction).map_err(|e| e.to_string())?;
+
+    words.insert(0, instruction);
+    return Ok(words);
+}
+
+fn assemble(line: &str) -> Result<Vec<isize>, String> {
+    let mut words = vec![];
+    for part in line.split(';') {
+        words.extend(assemble_one(part)?);
+    }
+    return Ok(words);
+}
+
+fn load(path: &str) -> Result<IntcodeProcessor, String> {
+
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+
+    let program = text.lines().next().unwrap_or("").trim()
+        .split(",")
+        .enumerate()
+        .map(|(addr, word)| word.trim().parse::<isize>().map_err(|_| format!("bad word @ {}: {}", addr, word)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    return Ok(IntcodeProcessor::new(&program));
+}
+
+fn show_memory(computer: &IntcodeProcessor, from: usize, to: usize) {
+
+    let cells = computer.memory().iter()
+        .filter(|(a, _)| from <= **a && **a <= to)
+        .map(|(a, w)| (*a, *w))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut row: Option<usize> = None;
+    for (address, word) in cells {
+        if row != Some(address / 10) {
+            if row.is_some() {
+                println!();
+            }
+            row = Some(address / 10);
+            print!("{:>6}:", address - address % 10);
+        }
+        print!(" [{}]={}", address, word);
+    }
+    if row.is_some() {
+        println!();
+    }
+}
+
+fn show_registers(computer: &IntcodeProcessor) {
+    let instruction = computer.memory().get(&computer.program_counter())
+        .map(|w| w.to_string())
+        .unwrap_or(String::from("-"));
+    println!("pc={} rb={} instruction={}", computer.program_counter(), computer.relative_base(), instruction);
+}
+
+// Steps the machine, then prints every memory cell and register that changed.
+// Returns whether it was still running when `limit` ran out, or the error if
+// the input typed for it wasn't a number (the input instruction is left to
+// run again).
+fn execute(computer: &mut IntcodeProcessor, limit: usize, stop: Option<(usize, usize)>) -> Result<bool, String> {
+
+    let before = computer.memory().clone();
+    let pc = computer.program_counter();
+
+    let mut steps = 0;
+    let mut halted = false;
+    let mut faulted = false;
+    let mut error = None;
+    while steps < limit {
+        if let Some((from, to)) = stop {
+            if steps > 0 && (computer.program_counter() < from || computer.program_counter() >= to) {
+                break;
+            }
+        }
+        // a bad instruction panics inside the processor; keep the session alive
+        let state = panic::catch_unwind(panic::AssertUnwindSafe(|| computer.step()));
+        while let Some(value) = computer.pop_output() {
+            output(value);
+        }
+        match state {
+            Ok(State::Running) => {},
+            // the input instruction runs again once there's something to read
+            Ok(State::AwaitingInput) => match input() {
+                Ok(Some(value)) => {
+                    computer.push_input(value);
+                    continue;
+                },
+                Ok(None) => {
+                    println!();
+                    break;
+                },
+                Err(err) => {
+                    error = Some(err);
+                    break;
+                },
+            },
+            Ok(State::Halted) => {
+                halted = true;
+                break;
+            },
+            Err(_) => {
+                faulted = true;
+                break;
+            },
+        }
+        steps += 1;
+    }
+
+    let changed = computer.memory().iter()
+        .filter(|(a, w)| before.get(a) != Some(w))
+        .map(|(a, w)| (*a, *w))
+        .collect::<BTreeMap<_, _>>();
+
+    for (address, word) in changed {
+        match before.get(&address) {
+            Some(old) => println!("  [{}] {} -> {}", address, old, word),
+            None => println!("  [{}] _ -> {}", address, word),
+        }
+    }
+
+    println!("  pc {} -> {} ({} step{}{})", pc, computer.program_counter(), steps,
+        if steps == 1 { "" } else { "s" },
+        if halted { ", halted" } else if faulted { ", faulted" } else { "" });
+
+    return match error {
+        Some(err) => Err(err),
+        None => Ok(steps == limit && !halted),
+    };
+}
+
+fn count(arg: Option<&&str>, default: usize) -> Result<usize, String> {
+    return match arg {
+        Some(n) => n.parse::<usize>().map_err(|_| format!("bad step count: {}", n)),
+        None => Ok(default),
+    };
+}
+
+fn command(computer: &mut IntcodeProcessor, line: &str) -> Result<bool, String> {
+
+    let args = line.split_whitespace().collect::<Vec<_>>();
+
+    match args[0] {
+        ":quit" | ":q" => return Ok(false),
+        ":help" | ":h" => println!("{}", HELP),
+        ":reset" => *computer = machine(),
+        ":load" => {
+            let path = args.get(1).ok_or("usage: :load <file>")?;
+            *computer = load(path)?;
+            println!("loaded {} words", computer.memory().len());
+        },
+        ":step" => {
+            execute(computer, count(args.get(1), 1)?, None)?;
+        },
+        ":run" => {
+            let limit = count(args.get(1), RUN_LIMIT)?;
+            if execute(computer, limit, None)? {
+                println!("  stopped after {} steps without halting (:run again to continue)", limit);
+            }
+        },
+        ":mem" => {
+            let from = args.get(1).map(|a| a.parse::<usize>()).unwrap_or(Ok(0)).map_err(|e| e.to_string())?;
+            let to = args.get(2).map(|a| a.parse::<usize>()).unwrap_or(Ok(usize::MAX)).map_err(|e| e.to_string())?;
+            show_memory(computer, from, to);
+        },
+        ":regs" => show_registers(computer),
+        x => return Err(format!("unknown command {} (try :help)", x)),
+    }
+
+    return Ok(true);
+}
+
+pub fn run() {
+
+    let mut computer = machine();
+
+    println!("Intcode REPL, :help for commands");
+
+    loop {
+        print!("intcode> ");
+        io::stdout().flush().unwrap();
+
+        let line = match io::stdin().lock().lines().next() {
+            Some(line) => line.unwrap(),
+            None => break,
+        };
+        let line = line.trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with(':') {
+            match command(&mut computer, line) {
+                Ok(true) => continue,
+                Ok(false) => break,
+                Err(err) => {
+                    println!("error: {}", err);
+                    continue;
+                },
+            }
+        }
+
+        match assemble(line) {
+            Ok(words) => {
+                let start = computer.program_counter();
+                for (i, word) in words.iter().enumerate() {
+                    computer.store(start + i, *word);
+                }
+                match execute(&mut computer, STEP_LIMIT, Some((start, start + words.len()))) {
+                    Ok(true) => println!("  stopped after {} steps", STEP_LIMIT),
+                    Ok(false) => {},
+                    Err(err) => println!("error: {}", err),
+                }
+            },
+            Err(err) => println!("error: {}", err),
+        }
+    }
+}
@@ -0,0 +1,285 @@
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process;
+
+use crate::intcode::IntcodeProcessor;
+use crate::intcode::State;
+use crate::instruction::Instruction;
+use crate::instruction::Mode;
+use crate::instruction::Opcode;
+use crate::instruction::Param;
+
+// The decoder the processor uses, compiled into every transpiled program so
+// that it decodes instructions the same way.
+const DECODER: &str = include_str!("instruction.rs");
+
+// Shared by every transpiled program: memory access with the same failure
+// behaviour as `IntcodeProcessor::fetch`, and a generic single-step
+// interpreter that runs any instruction the specialised arms don't cover,
+// either because it was never decoded or because its words were patched
+// at runtime.
+const RUNTIME: &str = r#"use std::io;
+use std::io::BufRead;
+
+use instruction::Instruction;
+use instruction::Mode;
+use instruction::Opcode;
+use instruction::Param;
+
+fn input() -> isize {
+    return io::stdin().lock().lines().next().unwrap().unwrap()
+        .parse::<isize>()
+        .unwrap();
+}
+
+fn output(val: isize) {
+    println!("{}", val);
+}
+
+// negative addresses wrap around to the top half of usize
+fn check_address(address: usize) {
+    if address > isize::MAX as usize {
+        panic!("Invalid memory access @ {}", address as isize);
+    }
+}
+
+fn fetch(memory: &Vec<Option<isize>>, address: usize) -> isize {
+    check_address(address);
+    return match memory.get(address) {
+        Some(Some(word)) => *word,
+        _ => 0,
+    };
+}
+
+fn store(memory: &mut Vec<Option<isize>>, address: usize, word: isize) {
+    check_address(address);
+    if address >= memory.len() {
+        memory.resize(address + 1, None);
+    }
+    memory[address] = Some(word);
+}
+
+fn intact(memory: &Vec<Option<isize>>, address: usize, words: &[isize]) -> bool {
+    return words.iter().enumerate().all(|(i, w)| memory.get(address + i) == Some(&Some(*w)));
+}
+
+fn val(memory: &Vec<Option<isize>>, instruction: &Instruction, rb: isize, pc: usize, position: usize) -> isize {
+    let value = fetch(memory, pc + position);
+    return match instruction.params()[position - 1] {
+        Param::Read(Mode::Position) => fetch(memory, value as usize),
+        Param::Read(Mode::Immediate) => value,
+        Param::Read(Mode::Relative) => fetch(memory, (rb + value) as usize),
+        Param::Write(_) => panic!("parameter {} is written, not read (@ {})", position, pc),
+    };
+}
+
+fn dst(memory: &Vec<Option<isize>>, instruction: &Instruction, rb: isize, pc: usize, position: usize) -> usize {
+    let value = fetch(memory, pc + position);
+    return match instruction.params()[position - 1] {
+        Param::Write(Mode::Position) => value as usize,
+        Param::Write(Mode::Relative) => (rb + value) as usize,
+        _ => panic!("parameter {} is not a write target (@ {})", position, pc),
+    };
+}
+
+fn step(memory: &mut Vec<Option<isize>>, rb: &mut isize, pc: usize) -> Option<usize> {
+    let word = fetch(memory, pc);
+    let i = match Instruction::decode(word) {
+        Ok(instruction) => instruction,
+        Err(e) => panic!("Invalid instruction {} @ {}: {}", word, pc, e),
+    };
+    let (b, next) = (*rb, pc + i.len());
+    return match i.opcode {
+        Opcode::Add => { let v = val(memory, &i, b, pc, 1) + val(memory, &i, b, pc, 2); let d = dst(memory, &i, b, pc, 3); store(memory, d, v); Some(next) },
+        Opcode::Mul => { let v = val(memory, &i, b, pc, 1) * val(memory, &i, b, pc, 2); let d = dst(memory, &i, b, pc, 3); store(memory, d, v); Some(next) },
+        Opcode::Input => { let d = dst(memory, &i, b, pc, 1); store(memory, d, input()); Some(next) },
+        Opcode::Output => { output(val(memory, &i, b, pc, 1)); Some(next) },
+        Opcode::JumpIfTrue => match val(memory, &i, b, pc, 1) { 0 => Some(next), _ => Some(val(memory, &i, b, pc, 2) as usize) },
+        Opcode::JumpIfFalse => match val(memory, &i, b, pc, 1) { 0 => Some(val(memory, &i, b, pc, 2) as usize), _ => Some(next) },
+        Opcode::LessThan => { let v = (val(memory, &i, b, pc, 1) < val(memory, &i, b, pc, 2)) as isize; let d = dst(memory, &i, b, pc, 3); store(memory, d, v); Some(next) },
+        Opcode::Equals => { let v = (val(memory, &i, b, pc, 1) == val(memory, &i, b, pc, 2)) as isize; let d = dst(memory, &i, b, pc, 3); store(memory, d, v); Some(next) },
+        Opcode::AdjustBase => { *rb += val(memory, &i, b, pc, 1); Some(next) },
+        Opcode::Halt => None,
+        // the decoder only produces standard opcodes
+        Opcode::Custom(_) => unreachable!(),
+    };
+}
+"#;
+
+// Renders the arm body for the instruction at `pc`, or `None` when the words
+// there don't form a valid instruction (in which case the generic `step`
+// handles the address if it's ever reached).
+fn compile(program: &[isize], pc: usize) -> Option<String> {
+
+    let instruction = Instruction::decode(program[pc]).ok()?;
+    let len = instruction.len();
+    if pc + len > program.len() {
+        return None;
+    }
+
+    let val = |p: usize| -> Option<String> {
+        let value = program[pc + p];
+        return match instruction.params()[p - 1] {
+            Param::Read(Mode::Position) if value >= 0 => Some(format!("fetch(&memory, {})", value)),
+            Param::Read(Mode::Immediate) => Some(format!("{}", value)),
+            Param::Read(Mode::Relative) => Some(format!("fetch(&memory, (rb + {}) as usize)", value)),
+            _ => None,
+        };
+    };
+
+    let dst = |p: usize| -> Option<String> {
+        let value = program[pc + p];
+        return match instruction.params()[p - 1] {
+            Param::Write(Mode::Position) if value >= 0 => Some(value.to_string()),
+            Param::Write(Mode::Relative) => Some(format!("(rb + {}) as usize", value)),
+            _ => None,
+        };
+    };
+
+    let next = pc + len;
+
+    let body = match instruction.opcode {
+        Opcode::Add => format!("{{ let v = {} + {}; store(&mut memory, {}, v); {} }}", val(1)?, val(2)?, dst(3)?, next),
+        Opcode::Mul => format!("{{ let v = {} * {}; store(&mut memory, {}, v); {} }}", val(1)?, val(2)?, dst(3)?, next),
+        Opcode::Input => format!("{{ store(&mut memory, {}, input()); {} }}", dst(1)?, next),
+        Opcode::Output => format!("{{ output({}); {} }}", val(1)?, next),
+        Opcode::JumpIfTrue => format!("match {} {{ 0 => {}, _ => {} as usize }}", val(1)?, next, val(2)?),
+        Opcode::JumpIfFalse => format!("match {} {{ 0 => {} as usize, _ => {} }}", val(1)?, val(2)?, next),
+        Opcode::LessThan => format!("{{ let v = ({} < {}) as isize; store(&mut memory, {}, v); {} }}", val(1)?, val(2)?, dst(3)?, next),
+        Opcode::Equals => format!("{{ let v = ({} == {}) as isize; store(&mut memory, {}, v); {} }}", val(1)?, val(2)?, dst(3)?, next),
+        Opcode::AdjustBase => format!("{{ rb += {}; {} }}", val(1)?, next),
+        Opcode::Halt => String::from("break"),
+        Opcode::Custom(_) => return None,
+    };
+
+    let words = program[pc..next].iter()
+        .map(|w| w.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    return Some(format!("            {} if intact(&memory, {}, &[{}]) => {},\n", pc, pc, words, body));
+}
+
+pub fn transpile(program: &[isize]) -> String {
+
+    let mut source = String::from(RUNTIME);
+
+    source.push_str(&format!("\nmod instruction {{\n{}}}\n", DECODER));
+
+    let words = program.iter()
+        .map(|w| w.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    source.push_str(&format!("\nconst PROGRAM: [isize; {}] = [{}];\n\n", program.len(), words));
+    source.push_str("fn main() {\n\n");
+    source.push_str("    let mut memory = PROGRAM.iter().map(|w| Some(*w)).collect::<Vec<_>>();\n");
+    source.push_str("    let mut pc: usize = 0;\n");
+    source.push_str("    let mut rb: isize = 0;\n\n");
+    source.push_str("    loop {\n");
+    source.push_str("        pc = match pc {\n");
+
+    for pc in 0..program.len() {
+        if let Some(arm) = compile(program, pc) {
+            source.push_str(&arm);
+        }
+    }
+
+    source.push_str("            _ => match step(&mut memory, &mut rb, pc) {\n");
+    source.push_str("                Some(next) => next,\n");
+    source.push_str("                None => break,\n");
+    source.push_str("            },\n");
+    source.push_str("        };\n");
+    source.push_str("    }\n");
+    source.push_str("}\n");
+
+    return source;
+}
+
+fn interpret(program: &[isize], inputs: &[isize]) -> Result<Vec<isize>, String> {
+
+    let mut computer = IntcodeProcessor::new(program);
+    let mut inputs = inputs.iter();
+
+    while computer.run() == State::AwaitingInput {
+        match inputs.next() {
+            Some(value) => computer.push_input(*value),
+            None => return Err(String::from("interpreter ran out of input")),
+        }
+    }
+
+    let mut outputs = vec![];
+    while let Some(value) = computer.pop_output() {
+        outputs.push(value);
+    }
+
+    return Ok(outputs);
+}
+
+// The transpiled source and its binary, removed again when dropped so that
+// nothing is left behind however `native` returns.
+struct Scratch {
+    src: PathBuf,
+    bin: PathBuf,
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.src);
+        let _ = fs::remove_file(&self.bin);
+    }
+}
+
+fn native(program: &[isize], inputs: &[isize]) -> Result<Vec<isize>, String> {
+
+    let dir = env::temp_dir();
+    let scratch = Scratch {
+        src: dir.join(format!("aoc-transpiled-{}.rs", process::id())),
+        bin: dir.join(format!("aoc-transpiled-{}", process::id())),
+    };
+    let (src, bin) = (&scratch.src, &scratch.bin);
+
+    fs::write(src, transpile(program)).map_err(|e| e.to_string())?;
+
+    let status = process::Command::new("rustc")
+        .args(["-O", "-o"]).arg(bin).arg(src)
+        .status()
+        .map_err(|e| e.to_string())?;
+    if !status.success() {
+        return Err(String::from("failed to compile the transpiled program"));
+    }
+
+    let mut child = process::Command::new(bin)
+        .stdin(process::Stdio::piped())
+        .stdout(process::Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    let stdin = inputs.iter().map(|i| format!("{}\n", i)).collect::<String>();
+    child.stdin.take().unwrap().write_all(stdin.as_bytes()).map_err(|e| e.to_string())?;
+
+    let out = child.wait_with_output().map_err(|e| e.to_string())?;
+    if !out.status.success() {
+        return Err(String::from("transpiled program failed"));
+    }
+
+    return String::from_utf8_lossy(&out.stdout).lines()
+        .map(|l| l.parse::<isize>().map_err(|e| format!("unexpected output {:?}: {}", l, e)))
+        .collect();
+}
+
+// Runs the program through both the interpreter and its transpiled, natively
+// compiled form with the same inputs, and reports whether they agree.
+pub fn verify(program: &[isize], inputs: &[isize]) -> Result<Vec<isize>, String> {
+
+    let expected = interpret(program, inputs)?;
+    let actual = native(program, inputs)?;
+
+    if expected != actual {
+        return Err(format!("output mismatch: interpreter {:?}, transpiled {:?}", expected, actual));
+    }
+
+    return Ok(expected);
+}
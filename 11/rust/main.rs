@@ -0,0 +1,165 @@
+mod instruction;
+mod intcode;
+
+use std::collections::HashMap;
+use std::io;
+use std::io::BufRead;
+
+use intcode::IntcodeProcessor;
+use intcode::State;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Colour { Black, White }
+
+impl Colour {
+
+    fn from(value: isize) -> Colour {
+        return match value {
+            0 => Colour::Black,
+            1 => Colour::White,
+            x => panic!("invalid colour {}", x),
+        };
+    }
+
+    fn value(&self) -> isize {
+        return match self {
+            Colour::Black => 0,
+            Colour::White => 1,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Direction { Left, Right, Up, Down }
+
+impl Direction {
+
+    fn turn(&self, value: isize) -> Direction {
+        return match (value, self) {
+            (0, Direction::Up) => Direction::Left,
+            (0, Direction::Left) => Direction::Down,
+            (0, Direction::Down) => Direction::Right,
+            (0, Direction::Right) => Direction::Up,
+            (1, Direction::Up) => Direction::Right,
+            (1, Direction::Right) => Direction::Down,
+            (1, Direction::Down) => Direction::Left,
+            (1, Direction::Left) => Direction::Up,
+            (x, _) => panic!("invalid turn {}", x),
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Coordinate {
+    x: i64,
+    y: i64,
+}
+
+impl Coordinate {
+
+    fn new(x: i64, y: i64) -> Coordinate {
+        return Coordinate { x, y };
+    }
+
+    fn step(&self, direction: Direction) -> Coordinate {
+        return match direction {
+            Direction::Left => Coordinate::new(self.x - 1, self.y),
+            Direction::Right => Coordinate::new(self.x + 1, self.y),
+            Direction::Up => Coordinate::new(self.x, self.y + 1),
+            Direction::Down => Coordinate::new(self.x, self.y - 1),
+        };
+    }
+}
+
+struct Robot {
+    brain: IntcodeProcessor,
+    position: Coordinate,
+    facing: Direction,
+    // panels that were never painted are black and absent from the map
+    hull: HashMap<Coordinate, Colour>,
+}
+
+impl Robot {
+
+    fn new(program: &[isize]) -> Robot {
+        return Robot {
+            brain: IntcodeProcessor::new(program),
+            position: Coordinate::new(0, 0),
+            facing: Direction::Up,
+            hull: HashMap::new(),
+        };
+    }
+
+    fn colour(&self, at: &Coordinate) -> Colour {
+        return self.hull.get(at).cloned().unwrap_or(Colour::Black);
+    }
+
+    fn run(&mut self) {
+        loop {
+            self.brain.push_input(self.colour(&self.position).value());
+
+            let state = self.brain.run();
+
+            while let Some(paint) = self.brain.pop_output() {
+                let turn = self.brain.pop_output().expect("robot painted without turning");
+                self.hull.insert(self.position, Colour::from(paint));
+                self.facing = self.facing.turn(turn);
+                self.position = self.position.step(self.facing);
+            }
+
+            if state == State::Halted {
+                break;
+            }
+        }
+    }
+
+    fn render(&self) -> String {
+
+        let white = self.hull.iter()
+            .filter(|(_, c)| **c == Colour::White)
+            .map(|(k, _)| *k)
+            .collect::<Vec<_>>();
+
+        if white.is_empty() {
+            return String::new();
+        }
+
+        let min_x = white.iter().map(|c| c.x).min().unwrap();
+        let max_x = white.iter().map(|c| c.x).max().unwrap();
+        let min_y = white.iter().map(|c| c.y).min().unwrap();
+        let max_y = white.iter().map(|c| c.y).max().unwrap();
+
+        let mut text = String::new();
+
+        for y in (min_y..=max_y).rev() {
+            for x in min_x..=max_x {
+                text.push(match self.colour(&Coordinate::new(x, y)) {
+                    Colour::White => '#',
+                    Colour::Black => ' ',
+                });
+            }
+            text.push('\n');
+        }
+
+        return text;
+    }
+}
+
+fn main() {
+
+    let program = intcode::read_program(&io::stdin().lock().lines().next().unwrap().unwrap());
+
+    let mut robot = Robot::new(&program);
+    robot.run();
+
+    let part1 = robot.hull.len();
+
+    let mut robot = Robot::new(&program);
+    robot.hull.insert(Coordinate::new(0, 0), Colour::White);
+    robot.run();
+
+    println!("part 1: {}", part1);
+
+    println!("part 2:");
+    print!("{}", robot.render());
+}
@@ -0,0 +1,215 @@
+mod instruction;
+mod intcode;
+
+use std::collections::HashMap;
+use std::env;
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+use std::thread;
+use std::time::Duration;
+
+use intcode::IntcodeProcessor;
+use intcode::State;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tile { Empty, Wall, Block, Paddle, Ball }
+
+impl Tile {
+
+    fn from(value: isize) -> Tile {
+        return match value {
+            0 => Tile::Empty,
+            1 => Tile::Wall,
+            2 => Tile::Block,
+            3 => Tile::Paddle,
+            4 => Tile::Ball,
+            x => panic!("invalid tile {}", x),
+        };
+    }
+
+    fn glyph(&self) -> char {
+        return match self {
+            Tile::Empty => ' ',
+            Tile::Wall => '#',
+            Tile::Block => '=',
+            Tile::Paddle => '-',
+            Tile::Ball => 'o',
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Coordinate {
+    x: i64,
+    y: i64,
+}
+
+impl Coordinate {
+    fn new(x: i64, y: i64) -> Coordinate {
+        return Coordinate { x, y };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Control { Auto, Keyboard }
+
+struct Cabinet {
+    cpu: IntcodeProcessor,
+    screen: HashMap<Coordinate, Tile>,
+    score: isize,
+    ball: Option<Coordinate>,
+    paddle: Option<Coordinate>,
+}
+
+impl Cabinet {
+
+    fn new(program: &[isize], free_play: bool) -> Cabinet {
+
+        let mut cpu = IntcodeProcessor::new(program);
+
+        if free_play {
+            cpu.store(0, 2);
+        }
+
+        return Cabinet { cpu, screen: HashMap::new(), score: 0, ball: None, paddle: None };
+    }
+
+    // Runs until the game wants joystick input or is over, applying every
+    // draw and score update it produced along the way.
+    fn advance(&mut self) -> State {
+
+        let state = self.cpu.run();
+
+        while let Some(x) = self.cpu.pop_output() {
+            let y = self.cpu.pop_output().expect("incomplete draw instruction");
+            let value = self.cpu.pop_output().expect("incomplete draw instruction");
+
+            if x == -1 && y == 0 {
+                self.score = value;
+                continue;
+            }
+
+            let at = Coordinate::new(x as i64, y as i64);
+            let tile = Tile::from(value);
+            match tile {
+                Tile::Ball => self.ball = Some(at),
+                Tile::Paddle => self.paddle = Some(at),
+                _ => {},
+            }
+            self.screen.insert(at, tile);
+        }
+
+        return state;
+    }
+
+    fn blocks(&self) -> usize {
+        return self.screen.values().filter(|t| **t == Tile::Block).count();
+    }
+
+    // Keeps the paddle directly under the ball.
+    fn autoplay(&self) -> isize {
+        return match (self.ball, self.paddle) {
+            (Some(ball), Some(paddle)) => (ball.x - paddle.x).signum() as isize,
+            _ => 0,
+        };
+    }
+
+    fn render(&self) -> String {
+
+        let width = self.screen.keys().map(|c| c.x).max().unwrap_or(0);
+        let height = self.screen.keys().map(|c| c.y).max().unwrap_or(0);
+
+        let mut text = format!("score: {}   blocks: {}\n", self.score, self.blocks());
+
+        for y in 0..=height {
+            for x in 0..=width {
+                let tile = self.screen.get(&Coordinate::new(x, y)).cloned().unwrap_or(Tile::Empty);
+                text.push(tile.glyph());
+            }
+            text.push('\n');
+        }
+
+        return text;
+    }
+
+    fn play(&mut self, control: Control, show: bool) -> isize {
+
+        loop {
+            let state = self.advance();
+
+            if show {
+                // clear the terminal and redraw from the top left
+                print!("\x1b[2J\x1b[H{}", self.render());
+                io::stdout().flush().unwrap();
+            }
+
+            if state == State::Halted {
+                return self.score;
+            }
+
+            let joystick = match control {
+                Control::Auto => {
+                    if show {
+                        thread::sleep(Duration::from_millis(20));
+                    }
+                    self.autoplay()
+                },
+                Control::Keyboard => read_joystick(),
+            };
+
+            self.cpu.push_input(joystick);
+        }
+    }
+}
+
+// One line per move: `a` tilts left, `d` tilts right, anything else (including
+// an empty line) leaves the joystick neutral.
+fn read_joystick() -> isize {
+
+    print!("[a] left  [d] right  [enter] stay > ");
+    io::stdout().flush().unwrap();
+
+    let line = match io::stdin().lock().lines().next() {
+        Some(line) => line.unwrap(),
+        None => panic!("keyboard input closed"),
+    };
+
+    return match line.trim() {
+        "a" | "h" => -1,
+        "d" | "l" => 1,
+        _ => 0,
+    };
+}
+
+fn main() {
+
+    let mode = env::args().nth(1);
+
+    let program = intcode::read_program(&io::stdin().lock().lines().next().unwrap().unwrap());
+
+    match mode.as_deref() {
+        Some("play") => {
+            let score = Cabinet::new(&program, true).play(Control::Keyboard, true);
+            println!("game over, score: {}", score);
+            return;
+        },
+        Some("watch") => {
+            let score = Cabinet::new(&program, true).play(Control::Auto, true);
+            println!("game over, score: {}", score);
+            return;
+        },
+        _ => {},
+    }
+
+    let mut cabinet = Cabinet::new(&program, false);
+    cabinet.advance();
+
+    let part1 = cabinet.blocks();
+
+    let part2 = Cabinet::new(&program, true).play(Control::Auto, false);
+
+    println!("part 1: {}", part1);
+
+    println!("part 2: {}", part2);
+}
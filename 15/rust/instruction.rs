@@ -0,0 +1,236 @@
+// Generated from 05/rust/instruction.rs by ./sync-intcode, edit the original instead.
+
+// Copied to the other Intcode days along with intcode.rs, which don't need
+// all of it.
+#![allow(dead_code)]
+
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    Add,
+    Mul,
+    Input,
+    Output,
+    JumpIfTrue,
+    JumpIfFalse,
+    LessThan,
+    Equals,
+    AdjustBase,
+    Halt,
+    // an extension instruction registered with the processor
+    Custom(isize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Position,
+    Immediate,
+    Relative,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Param {
+    Read(Mode),
+    Write(Mode),
+}
+
+const STANDARD: [Opcode; 10] = [
+    Opcode::Add,
+    Opcode::Mul,
+    Opcode::Input,
+    Opcode::Output,
+    Opcode::JumpIfTrue,
+    Opcode::JumpIfFalse,
+    Opcode::LessThan,
+    Opcode::Equals,
+    Opcode::AdjustBase,
+    Opcode::Halt,
+];
+
+impl Opcode {
+
+    pub fn from_code(code: isize) -> Option<Opcode> {
+        return match code {
+            1 => Some(Opcode::Add),
+            2 => Some(Opcode::Mul),
+            3 => Some(Opcode::Input),
+            4 => Some(Opcode::Output),
+            5 => Some(Opcode::JumpIfTrue),
+            6 => Some(Opcode::JumpIfFalse),
+            7 => Some(Opcode::LessThan),
+            8 => Some(Opcode::Equals),
+            9 => Some(Opcode::AdjustBase),
+            99 => Some(Opcode::Halt),
+            _ => None,
+        };
+    }
+
+    pub fn from_mnemonic(mnemonic: &str) -> Option<Opcode> {
+        return STANDARD.iter().cloned().find(|op| op.mnemonic() == mnemonic);
+    }
+
+    pub fn code(&self) -> isize {
+        return match self {
+            Opcode::Add => 1,
+            Opcode::Mul => 2,
+            Opcode::Input => 3,
+            Opcode::Output => 4,
+            Opcode::JumpIfTrue => 5,
+            Opcode::JumpIfFalse => 6,
+            Opcode::LessThan => 7,
+            Opcode::Equals => 8,
+            Opcode::AdjustBase => 9,
+            Opcode::Halt => 99,
+            Opcode::Custom(x) => *x,
+        };
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        return match self {
+            Opcode::Add => "add",
+            Opcode::Mul => "mul",
+            Opcode::Input => "in",
+            Opcode::Output => "out",
+            Opcode::JumpIfTrue => "jnz",
+            Opcode::JumpIfFalse => "jz",
+            Opcode::LessThan => "lt",
+            Opcode::Equals => "eq",
+            Opcode::AdjustBase => "arb",
+            Opcode::Halt => "hlt",
+            Opcode::Custom(_) => "ext",
+        };
+    }
+
+    pub fn parameters(&self) -> &'static [Access] {
+        return match self {
+            Opcode::Add | Opcode::Mul | Opcode::LessThan | Opcode::Equals =>
+                &[Access::Read, Access::Read, Access::Write],
+            Opcode::Input => &[Access::Write],
+            Opcode::Output | Opcode::AdjustBase => &[Access::Read],
+            Opcode::JumpIfTrue | Opcode::JumpIfFalse => &[Access::Read, Access::Read],
+            Opcode::Halt | Opcode::Custom(_) => &[],
+        };
+    }
+}
+
+// No standard instruction takes more than three parameters, and extensions
+// are held to the same limit.
+pub const MAX_PARAMS: usize = 3;
+
+// The parameters sit in a fixed array so that decoding doesn't allocate;
+// only the first `count` are used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    pub opcode: Opcode,
+    params: [Param; MAX_PARAMS],
+    count: usize,
+}
+
+impl Instruction {
+
+    pub fn decode(word: isize) -> Result<Instruction, DecodeError> {
+        let opcode = match word {
+            w if w < 0 => return Err(DecodeError::new(&word, DecodeErrorKind::Negative)),
+            w => Opcode::from_code(w % 100)
+                .ok_or(DecodeError::new(&word, DecodeErrorKind::UnknownOpcode(w % 100)))?,
+        };
+        return Instruction::decode_as(word, opcode, opcode.parameters());
+    }
+
+    // Decodes a word whose opcode isn't part of the standard set, given the
+    // parameters the extension declared.
+    pub fn decode_custom(word: isize, parameters: &[Access]) -> Result<Instruction, DecodeError> {
+        if word < 0 {
+            return Err(DecodeError::new(&word, DecodeErrorKind::Negative));
+        }
+        if parameters.len() > MAX_PARAMS {
+            return Err(DecodeError::new(&word, DecodeErrorKind::TooManyParameters(parameters.len())));
+        }
+        return Instruction::decode_as(word, Opcode::Custom(word % 100), parameters);
+    }
+
+    fn decode_as(word: isize, opcode: Opcode, parameters: &[Access]) -> Result<Instruction, DecodeError> {
+
+        let mut modes = word / 100;
+        let mut params = [Param::Read(Mode::Immediate); MAX_PARAMS];
+
+        for (i, access) in parameters.iter().enumerate() {
+            let position = i + 1;
+            let mode = match modes % 10 {
+                0 => Mode::Position,
+                1 => Mode::Immediate,
+                2 => Mode::Relative,
+                x => return Err(DecodeError::new(&word, DecodeErrorKind::UnknownMode { position, mode: x })),
+            };
+            params[i] = match (access, mode) {
+                (Access::Read, m) => Param::Read(m),
+                (Access::Write, Mode::Immediate) =>
+                    return Err(DecodeError::new(&word, DecodeErrorKind::ImmediateWrite { position })),
+                (Access::Write, m) => Param::Write(m),
+            };
+            modes /= 10;
+        }
+
+        if modes != 0 {
+            return Err(DecodeError::new(&word, DecodeErrorKind::StrayModeDigits));
+        }
+
+        return Ok(Instruction { opcode, params, count: parameters.len() });
+    }
+
+    pub fn params(&self) -> &[Param] {
+        return &self.params[..self.count];
+    }
+
+    pub fn len(&self) -> usize {
+        return 1 + self.count;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeErrorKind {
+    Negative,
+    UnknownOpcode(isize),
+    UnknownMode { position: usize, mode: isize },
+    ImmediateWrite { position: usize },
+    StrayModeDigits,
+    TooManyParameters(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeError {
+    pub word: isize,
+    pub kind: DecodeErrorKind,
+}
+
+impl DecodeError {
+    fn new(word: &isize, kind: DecodeErrorKind) -> DecodeError {
+        return DecodeError { word: *word, kind };
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self.kind {
+            DecodeErrorKind::Negative => write!(f, "{} is negative", self.word),
+            DecodeErrorKind::UnknownOpcode(x) => write!(f, "{} has unknown opcode {}", self.word, x),
+            DecodeErrorKind::UnknownMode { position, mode } =>
+                write!(f, "{} has unknown mode {} for parameter {}", self.word, mode, position),
+            DecodeErrorKind::ImmediateWrite { position } =>
+                write!(f, "{} writes to parameter {} in immediate mode", self.word, position),
+            DecodeErrorKind::StrayModeDigits => write!(f, "{} has mode digits beyond its parameters", self.word),
+            DecodeErrorKind::TooManyParameters(x) =>
+                write!(f, "{} declares {} parameters, at most {} are supported", self.word, x, MAX_PARAMS),
+        };
+    }
+}
+
+impl Error for DecodeError {}
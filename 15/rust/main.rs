@@ -0,0 +1,238 @@
+mod instruction;
+mod intcode;
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::env;
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+use std::thread;
+use std::time::Duration;
+
+use intcode::IntcodeProcessor;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction { North, South, West, East }
+
+const DIRECTIONS: [Direction; 4] = [Direction::North, Direction::South, Direction::West, Direction::East];
+
+impl Direction {
+
+    fn command(&self) -> isize {
+        return match self {
+            Direction::North => 1,
+            Direction::South => 2,
+            Direction::West => 3,
+            Direction::East => 4,
+        };
+    }
+
+    fn reverse(&self) -> Direction {
+        return match self {
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+            Direction::East => Direction::West,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Coordinate {
+    x: i64,
+    y: i64,
+}
+
+impl Coordinate {
+
+    fn new(x: i64, y: i64) -> Coordinate {
+        return Coordinate { x, y };
+    }
+
+    fn step(&self, direction: Direction) -> Coordinate {
+        return match direction {
+            Direction::North => Coordinate::new(self.x, self.y + 1),
+            Direction::South => Coordinate::new(self.x, self.y - 1),
+            Direction::West => Coordinate::new(self.x - 1, self.y),
+            Direction::East => Coordinate::new(self.x + 1, self.y),
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell { Wall, Open, Oxygen }
+
+struct Droid {
+    controller: IntcodeProcessor,
+    position: Coordinate,
+    map: HashMap<Coordinate, Cell>,
+    commands: usize,
+    show: bool,
+}
+
+impl Droid {
+
+    fn new(program: &[isize], show: bool) -> Droid {
+
+        let mut map = HashMap::new();
+        map.insert(Coordinate::new(0, 0), Cell::Open);
+
+        return Droid {
+            controller: IntcodeProcessor::new(program),
+            position: Coordinate::new(0, 0),
+            map,
+            commands: 0,
+            show,
+        };
+    }
+
+    // Sends one movement command and records what the droid reported about
+    // the cell in that direction. Returns whether the droid actually moved.
+    fn command(&mut self, direction: Direction) -> bool {
+
+        self.controller.push_input(direction.command());
+        self.controller.run();
+        self.commands += 1;
+
+        let target = self.position.step(direction);
+
+        let cell = match self.controller.pop_output() {
+            Some(0) => Cell::Wall,
+            Some(1) => Cell::Open,
+            Some(2) => Cell::Oxygen,
+            Some(x) => panic!("invalid droid status {}", x),
+            None => panic!("droid did not report a status"),
+        };
+
+        self.map.insert(target, cell);
+
+        if cell != Cell::Wall {
+            self.position = target;
+        }
+
+        if self.show {
+            print!("\x1b[2J\x1b[H{}", render(&self.map, Some(self.position)));
+            io::stdout().flush().unwrap();
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        return cell != Cell::Wall;
+    }
+
+    // Depth-first search that never copies the machine: the route back to
+    // the origin is the stack of moves taken, and backtracking replays it in
+    // reverse so the droid physically retraces its steps.
+    fn explore(&mut self) {
+
+        let mut route: Vec<Direction> = vec![];
+
+        loop {
+            let unexplored = DIRECTIONS.iter()
+                .cloned()
+                .find(|d| !self.map.contains_key(&self.position.step(*d)));
+
+            match unexplored {
+                Some(direction) => {
+                    if self.command(direction) {
+                        route.push(direction);
+                    }
+                },
+                None => match route.pop() {
+                    Some(direction) => {
+                        if !self.command(direction.reverse()) {
+                            panic!("droid could not retrace its steps at {:?}", self.position);
+                        }
+                    },
+                    None => return,
+                },
+            }
+        }
+    }
+}
+
+// Breadth-first distances over open cells from `from`.
+fn distances(map: &HashMap<Coordinate, Cell>, from: Coordinate) -> HashMap<Coordinate, usize> {
+
+    let mut seen = HashMap::new();
+    let mut queue = VecDeque::new();
+
+    seen.insert(from, 0);
+    queue.push_back(from);
+
+    while let Some(at) = queue.pop_front() {
+        let distance = seen[&at];
+        for direction in DIRECTIONS.iter() {
+            let next = at.step(*direction);
+            match map.get(&next) {
+                Some(Cell::Wall) | None => continue,
+                _ => {},
+            }
+            if let Entry::Vacant(cell) = seen.entry(next) {
+                cell.insert(distance + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    return seen;
+}
+
+fn render(map: &HashMap<Coordinate, Cell>, droid: Option<Coordinate>) -> String {
+
+    let min_x = map.keys().map(|c| c.x).min().unwrap_or(0);
+    let max_x = map.keys().map(|c| c.x).max().unwrap_or(0);
+    let min_y = map.keys().map(|c| c.y).min().unwrap_or(0);
+    let max_y = map.keys().map(|c| c.y).max().unwrap_or(0);
+
+    let mut text = String::new();
+
+    for y in (min_y..=max_y).rev() {
+        for x in min_x..=max_x {
+            let at = Coordinate::new(x, y);
+            text.push(match (Some(at) == droid, map.get(&at)) {
+                (true, _) => 'D',
+                (_, _) if x == 0 && y == 0 => 'S',
+                (_, Some(Cell::Wall)) => '#',
+                (_, Some(Cell::Open)) => '.',
+                (_, Some(Cell::Oxygen)) => 'O',
+                (_, None) => ' ',
+            });
+        }
+        text.push('\n');
+    }
+
+    return text;
+}
+
+fn main() {
+
+    let show = env::args().nth(1).as_deref() == Some("watch");
+
+    let program = intcode::read_program(&io::stdin().lock().lines().next().unwrap().unwrap());
+
+    let mut droid = Droid::new(&program, show);
+    droid.explore();
+
+    let oxygen = droid.map.iter()
+        .find(|(_, c)| **c == Cell::Oxygen)
+        .map(|(k, _)| *k)
+        .expect("no oxygen system found");
+
+    let part1 = distances(&droid.map, Coordinate::new(0, 0))[&oxygen];
+
+    // oxygen spreads one cell per minute, so the fill time is the distance to
+    // the open cell furthest from the oxygen system
+    let part2 = distances(&droid.map, oxygen).values().max().cloned().unwrap_or(0);
+
+    if !show {
+        print!("{}", render(&droid.map, None));
+    }
+
+    println!("part 1: {}", part1);
+
+    println!("part 2: {}", part2);
+
+    println!("{} droid commands", droid.commands);
+}
@@ -0,0 +1,265 @@
+mod ascii;
+mod instruction;
+mod intcode;
+
+use std::collections::HashSet;
+use std::env;
+use std::io;
+use std::io::BufRead;
+
+use intcode::IntcodeProcessor;
+use intcode::State;
+
+// Each movement function and the main routine may be at most this many
+// characters long, not counting the newline.
+const MEMORY_LIMIT: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction { Left, Right, Up, Down }
+
+impl Direction {
+
+    fn from(c: char) -> Option<Direction> {
+        return match c {
+            '<' => Some(Direction::Left),
+            '>' => Some(Direction::Right),
+            '^' => Some(Direction::Up),
+            'v' => Some(Direction::Down),
+            _ => None,
+        };
+    }
+
+    fn turn_left(&self) -> Direction {
+        return match self {
+            Direction::Up => Direction::Left,
+            Direction::Left => Direction::Down,
+            Direction::Down => Direction::Right,
+            Direction::Right => Direction::Up,
+        };
+    }
+
+    fn turn_right(&self) -> Direction {
+        return match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        };
+    }
+}
+
+// Camera image coordinates: x grows to the right and y grows downwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Coordinate {
+    x: i64,
+    y: i64,
+}
+
+impl Coordinate {
+
+    fn new(x: i64, y: i64) -> Coordinate {
+        return Coordinate { x, y };
+    }
+
+    fn step(&self, direction: Direction) -> Coordinate {
+        return match direction {
+            Direction::Left => Coordinate::new(self.x - 1, self.y),
+            Direction::Right => Coordinate::new(self.x + 1, self.y),
+            Direction::Up => Coordinate::new(self.x, self.y - 1),
+            Direction::Down => Coordinate::new(self.x, self.y + 1),
+        };
+    }
+}
+
+struct Camera {
+    scaffold: HashSet<Coordinate>,
+    robot: Coordinate,
+    facing: Direction,
+}
+
+impl Camera {
+
+    fn parse(image: &str) -> Camera {
+
+        let mut scaffold = HashSet::new();
+        let mut robot = None;
+
+        for (y, line) in image.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                let at = Coordinate::new(x as i64, y as i64);
+                if c == '#' {
+                    scaffold.insert(at);
+                }
+                if let Some(direction) = Direction::from(c) {
+                    scaffold.insert(at);
+                    robot = Some((at, direction));
+                }
+            }
+        }
+
+        let (robot, facing) = robot.expect("no vacuum robot in camera image");
+
+        return Camera { scaffold, robot, facing };
+    }
+
+    fn intersections(&self) -> Vec<Coordinate> {
+        return self.scaffold.iter()
+            .filter(|c| {
+                [Direction::Left, Direction::Right, Direction::Up, Direction::Down].iter()
+                    .all(|d| self.scaffold.contains(&c.step(*d)))
+            })
+            .cloned()
+            .collect();
+    }
+
+    // Follows the scaffold from the robot, going straight wherever possible
+    // and turning only at the ends of straight runs, until there's nowhere
+    // left to go. Each element is one turn followed by a forward move, e.g.
+    // `R,8`.
+    fn path(&self) -> Vec<String> {
+
+        let mut moves = vec![];
+        let mut position = self.robot;
+        let mut facing = self.facing;
+
+        loop {
+            let (turn, direction) = if self.scaffold.contains(&position.step(facing.turn_left())) {
+                ("L", facing.turn_left())
+            } else if self.scaffold.contains(&position.step(facing.turn_right())) {
+                ("R", facing.turn_right())
+            } else {
+                return moves;
+            };
+
+            let mut distance = 0;
+            while self.scaffold.contains(&position.step(direction)) {
+                position = position.step(direction);
+                distance += 1;
+            }
+
+            facing = direction;
+            moves.push(format!("{},{}", turn, distance));
+        }
+    }
+}
+
+struct Routines {
+    main: String,
+    functions: Vec<String>,
+}
+
+fn compress(moves: &[String]) -> Option<Routines> {
+
+    fn search<'a>(moves: &'a [String], at: usize, functions: &mut Vec<&'a [String]>, main: &mut Vec<usize>) -> bool {
+
+        if !main.is_empty() && main.len() * 2 - 1 > MEMORY_LIMIT {
+            return false;
+        }
+
+        if at == moves.len() {
+            return true;
+        }
+
+        for i in 0..functions.len() {
+            let f = functions[i];
+            if moves[at..].starts_with(f) {
+                main.push(i);
+                if search(moves, at + f.len(), functions, main) {
+                    return true;
+                }
+                main.pop();
+            }
+        }
+
+        if functions.len() < 3 {
+            for end in (at + 1)..=moves.len() {
+                let candidate = &moves[at..end];
+                if candidate.join(",").len() > MEMORY_LIMIT {
+                    break;
+                }
+                functions.push(candidate);
+                main.push(functions.len() - 1);
+                if search(moves, end, functions, main) {
+                    return true;
+                }
+                main.pop();
+                functions.pop();
+            }
+        }
+
+        return false;
+    }
+
+    let mut functions = vec![];
+    let mut main = vec![];
+
+    if !search(moves, 0, &mut functions, &mut main) {
+        return None;
+    }
+
+    let names = ["A", "B", "C"];
+
+    let mut functions = functions.iter().map(|f| f.join(",")).collect::<Vec<_>>();
+    while functions.len() < 3 {
+        // unused functions still have to be sent; any valid routine will do
+        functions.push(String::from("L,1"));
+    }
+
+    return Some(Routines {
+        main: main.iter().map(|i| names[*i]).collect::<Vec<_>>().join(","),
+        functions,
+    });
+}
+
+fn main() {
+
+    let video = env::args().nth(1).as_deref() == Some("watch");
+
+    let program = intcode::read_program(&io::stdin().lock().lines().next().unwrap().unwrap());
+
+    let mut computer = IntcodeProcessor::new(&program);
+    computer.run();
+    let (image, _) = ascii::receive(&mut computer);
+
+    let camera = Camera::parse(&image);
+
+    let part1: i64 = camera.intersections().iter()
+        .map(|c| c.x * c.y)
+        .sum();
+
+    let moves = camera.path();
+    let routines = compress(&moves).expect("path cannot be split into three movement functions");
+
+    print!("{}", image.trim_end());
+    println!();
+    println!("path: {}", moves.join(","));
+    println!("main: {}", routines.main);
+    for (name, function) in ["A", "B", "C"].iter().zip(routines.functions.iter()) {
+        println!("{}: {}", name, function);
+    }
+
+    let mut computer = IntcodeProcessor::new(&program);
+    computer.store(0, 2);
+
+    ascii::send_line(&mut computer, &routines.main);
+    for function in routines.functions.iter() {
+        ascii::send_line(&mut computer, function);
+    }
+    ascii::send_line(&mut computer, if video { "y" } else { "n" });
+
+    if computer.run() != State::Halted {
+        panic!("vacuum robot is still waiting for input");
+    }
+
+    let (text, raw) = ascii::receive(&mut computer);
+
+    if video {
+        print!("{}", text);
+    }
+
+    let part2 = raw.last().expect("vacuum robot reported no dust");
+
+    println!("part 1: {}", part1);
+
+    println!("part 2: {}", part2);
+}
@@ -0,0 +1,117 @@
+use crate::intcode::IntcodeProcessor;
+
+// The beam is never wider than this many cells per row of distance from the
+// emitter, which bounds the search along rows where it hasn't been seen yet.
+const SPREAD: i64 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Coordinate {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Coordinate {
+    pub fn new(x: i64, y: i64) -> Coordinate {
+        return Coordinate { x, y };
+    }
+}
+
+pub struct Scanner {
+    drone: IntcodeProcessor,
+    probes: usize,
+    // first and last affected x of each row scanned so far, `None` for rows
+    // close to the emitter where the beam falls between cells
+    rows: Vec<Option<(i64, i64)>>,
+}
+
+impl Scanner {
+
+    pub fn new(program: &[isize]) -> Scanner {
+        return Scanner { drone: IntcodeProcessor::new(program), probes: 0, rows: vec![] };
+    }
+
+    pub fn probes(&self) -> usize {
+        return self.probes;
+    }
+
+    // The drone program halts after a single reading, so every probe is a
+    // full run of a fresh copy of the machine.
+    pub fn probe(&mut self, at: Coordinate) -> bool {
+
+        let mut computer = self.drone.clone();
+        computer.push_input(at.x as isize);
+        computer.push_input(at.y as isize);
+        computer.run();
+
+        self.probes += 1;
+
+        return match computer.pop_output() {
+            Some(0) => false,
+            Some(1) => true,
+            Some(x) => panic!("invalid drone reading {}", x),
+            None => panic!("drone did not report a reading"),
+        };
+    }
+
+    // Both edges of the beam only ever move right from one row to the next,
+    // so each row is found by walking on from the previous row's edges rather
+    // than probing every cell.
+    pub fn row(&mut self, y: usize) -> Option<(i64, i64)> {
+
+        while self.rows.len() <= y {
+
+            let next = self.rows.len() as i64;
+
+            let (left, right) = self.rows.iter().rev()
+                .find_map(|r| *r)
+                .unwrap_or((0, 0));
+
+            let mut x = left;
+            while x <= (next + 1) * SPREAD && !self.probe(Coordinate::new(x, next)) {
+                x += 1;
+            }
+
+            if x > (next + 1) * SPREAD {
+                self.rows.push(None);
+                continue;
+            }
+
+            let start = x;
+            let mut end = right.max(start);
+            while self.probe(Coordinate::new(end + 1, next)) {
+                end += 1;
+            }
+
+            self.rows.push(Some((start, end)));
+        }
+
+        return self.rows[y];
+    }
+
+    pub fn count(&mut self, width: usize, height: usize) -> usize {
+        let width = width as i64;
+        return (0..height)
+            .filter_map(|y| self.row(y))
+            .filter(|(left, _)| *left < width)
+            .map(|(left, right)| (right.min(width - 1) - left + 1) as usize)
+            .sum();
+    }
+
+    // Finds the top left corner of the first square of the given size that
+    // fits entirely inside the beam, by checking whether the row `size - 1`
+    // above still reaches far enough right for each row's left edge.
+    pub fn fit(&mut self, size: usize) -> Coordinate {
+
+        let mut y = size - 1;
+
+        loop {
+            let top = y + 1 - size;
+            if let (Some((left, _)), Some((_, right))) = (self.row(y), self.row(top)) {
+                if right - left + 1 >= size as i64 {
+                    return Coordinate::new(left, top as i64);
+                }
+            }
+            y += 1;
+        }
+    }
+}
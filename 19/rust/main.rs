@@ -0,0 +1,31 @@
+mod beam;
+mod instruction;
+mod intcode;
+
+use std::io;
+use std::io::BufRead;
+
+use beam::Scanner;
+
+const WINDOW: usize = 50;
+const SHIP: usize = 100;
+
+fn main() {
+
+    let program = intcode::read_program(&io::stdin().lock().lines().next().unwrap().unwrap());
+
+    let mut scanner = Scanner::new(&program);
+
+    let part1 = scanner.count(WINDOW, WINDOW);
+    let window_probes = scanner.probes();
+
+    let corner = scanner.fit(SHIP);
+    let part2 = corner.x * 10000 + corner.y;
+
+    println!("part 1: {}", part1);
+
+    println!("part 2: {}", part2);
+
+    println!("{} drone probes, {} of them for the {} cell window",
+             scanner.probes(), window_probes, WINDOW * WINDOW);
+}
@@ -0,0 +1,162 @@
+mod ascii;
+mod instruction;
+mod intcode;
+mod simulator;
+mod springscript;
+
+use std::env;
+use std::fs;
+use std::io;
+use std::io::BufRead;
+
+use intcode::IntcodeProcessor;
+use intcode::State;
+use simulator::Search;
+use springscript::Mode;
+use springscript::Script;
+
+// scripts for the two parts, compiled in so the default run needs no files
+const WALK: &str = include_str!("walk.ss");
+const RUN: &str = include_str!("run.ss");
+
+enum Outcome {
+    Damage(isize),
+    // everything the droid printed after the prompt, and the bottom row of
+    // the last frame
+    Fell { frames: String, hull: String },
+}
+
+fn survey(program: &[isize], script: &Script) -> Outcome {
+
+    let mut droid = IntcodeProcessor::new(program);
+    droid.run();
+    // discard the "Input instructions:" prompt
+    ascii::receive(&mut droid);
+
+    for line in script.to_string().lines() {
+        ascii::send_line(&mut droid, line);
+    }
+
+    if droid.run() != State::Halted {
+        panic!("springdroid is still waiting for input");
+    }
+
+    let (text, raw) = ascii::receive(&mut droid);
+
+    if let Some(damage) = raw.last() {
+        return Outcome::Damage(*damage);
+    }
+
+    let hull = text.lines()
+        .rfind(|l| simulator::Hull::parse(l).is_some())
+        .unwrap_or("")
+        .to_string();
+
+    return Outcome::Fell { frames: text, hull };
+}
+
+fn load(path: &str) -> Script {
+
+    let text = fs::read_to_string(path).unwrap();
+
+    return match Script::parse(&text) {
+        Ok(script) => script,
+        Err(errors) => {
+            for error in errors {
+                eprintln!("{}: {}", path, error);
+            }
+            std::process::exit(1);
+        },
+    };
+}
+
+fn read_program() -> Vec<isize> {
+    return intcode::read_program(&io::stdin().lock().lines().next().unwrap().unwrap());
+}
+
+fn report(outcome: Outcome) -> isize {
+    return match outcome {
+        Outcome::Damage(damage) => damage,
+        Outcome::Fell { frames, hull } => {
+            print!("{}", frames);
+            eprintln!("springdroid fell into space over `{}`", hull);
+            std::process::exit(1);
+        },
+    };
+}
+
+fn main() {
+
+    let args = env::args().collect::<Vec<_>>();
+
+    match args.get(1).map(|s| s.as_str()) {
+        Some("check") => {
+            let script = load(&args[2]);
+            println!("{} instructions, {}", script.instructions.len(), script.mode.command());
+            return;
+        },
+        Some("run") => {
+            let script = load(&args[2]);
+            println!("hull damage: {}", report(survey(&read_program(), &script)));
+            return;
+        },
+        Some("test") => {
+            let script = load(&args[2]);
+            let hulls = simulator::parse_hulls(&fs::read_to_string(&args[3]).unwrap()).unwrap();
+            let mut failed = false;
+            for hull in hulls.iter() {
+                if let Some(at) = hull.traverse(|sensors| script.jumps(sensors)) {
+                    println!("fell at tile {}", at);
+                    failed = true;
+                } else {
+                    println!("made it across");
+                }
+            }
+            if failed {
+                std::process::exit(1);
+            }
+            return;
+        },
+        // search <WALK|RUN> <hulls> [sensors] [max instructions] [max states]
+        Some("search") => {
+            let mode = Mode::from(&args[2]).expect("mode must be WALK or RUN");
+            let hulls = simulator::parse_hulls(&fs::read_to_string(&args[3]).unwrap()).unwrap();
+            let sensors = args.get(4).cloned()
+                .unwrap_or(String::from(&"ABCDEFGHI"[..mode.sensors().min(simulator::MAX_SEARCH_SENSORS)]))
+                .chars()
+                .map(|c| match springscript::Register::from(&c.to_string()) {
+                    Some(springscript::Register::Sensor(i)) if i < mode.sensors() => i,
+                    _ => panic!("invalid sensor `{}` for {}", c, mode.command()),
+                })
+                .collect::<Vec<_>>();
+            let limit = args.get(5).map(|l| l.parse::<usize>().unwrap()).unwrap_or(springscript::MAX_INSTRUCTIONS);
+            let states = args.get(6).map(|l| l.parse::<usize>().unwrap()).unwrap_or(simulator::DEFAULT_SEARCH_STATES);
+            match simulator::search(mode, &sensors, &hulls, limit, states) {
+                Search::Found(script) => println!("{}", script),
+                Search::NotFound => {
+                    eprintln!("no script of up to {} instructions gets across every hull", limit);
+                    std::process::exit(1);
+                },
+                Search::GaveUp => {
+                    eprintln!("gave up after {} states without finding a script", states);
+                    std::process::exit(1);
+                },
+            }
+            return;
+        },
+        _ => {},
+    }
+
+    let program = read_program();
+
+    let walk = Script::parse(WALK).unwrap();
+    let run = Script::parse(RUN).unwrap();
+
+    let part1 = report(survey(&program, &walk));
+
+    let part2 = report(survey(&program, &run));
+
+    println!("part 1: {}", part1);
+
+    println!("part 2: {}", part2);
+}
@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::springscript::Instruction;
+use crate::springscript::Mode;
+use crate::springscript::Operation;
+use crate::springscript::Register;
+use crate::springscript::Script;
+use crate::springscript::MAX_INSTRUCTIONS;
+
+// Truth tables are stored one bit per combination of sensor readings, so at
+// most this many sensors can take part in a search.
+pub const MAX_SEARCH_SENSORS: usize = 7;
+
+// A stretch of hull as it appears on the bottom row of a springdroid frame,
+// e.g. `#####.#..########`, with the droid starting on the first tile.
+pub struct Hull {
+    ground: Vec<bool>,
+}
+
+impl Hull {
+
+    pub fn parse(line: &str) -> Option<Hull> {
+
+        if line.is_empty() || line.chars().any(|c| c != '#' && c != '.') {
+            return None;
+        }
+
+        return Some(Hull { ground: line.chars().map(|c| c == '#').collect() });
+    }
+
+    // Anything past the end of the pattern is taken to be solid ground.
+    fn ground(&self, at: usize) -> bool {
+        return self.ground.get(at).cloned().unwrap_or(true);
+    }
+
+    // Moves the droid one tile at a time, or four when it jumps, until it
+    // has cleared the pattern. Returns the tile it fell through, if any.
+    pub fn traverse<F: Fn(&[bool]) -> bool>(&self, jumps: F) -> Option<usize> {
+
+        let mut at = 0;
+
+        while at < self.ground.len() {
+
+            if !self.ground(at) {
+                return Some(at);
+            }
+
+            let sensors = (1..=9).map(|d| self.ground(at + d)).collect::<Vec<_>>();
+
+            at += if jumps(&sensors) { 4 } else { 1 };
+        }
+
+        return None;
+    }
+}
+
+// One hull pattern per line; blank lines and lines starting with `;` are
+// skipped.
+pub fn parse_hulls(text: &str) -> Result<Vec<Hull>, String> {
+    return text.lines()
+        .enumerate()
+        .map(|(n, l)| (n + 1, l.trim()))
+        .filter(|(_, l)| !l.is_empty() && !l.starts_with(';'))
+        .map(|(n, l)| Hull::parse(l).ok_or(format!("line {}: invalid hull pattern `{}`", n, l)))
+        .collect();
+}
+
+// Registers as truth tables: bit `c` is the register's value when the sensors
+// taking part in the search read combination `c`.
+type Table = u128;
+
+// How each pair of T and J truth tables was first reached, for rebuilding the
+// script that got there.
+type Parents = HashMap<(Table, Table), ((Table, Table), Instruction)>;
+
+// The search gives up after reaching this many distinct states unless told
+// otherwise, since each extra instruction can multiply the frontier.
+pub const DEFAULT_SEARCH_STATES: usize = 1_000_000;
+
+pub enum Search {
+    Found(Script),
+    // every script up to the instruction limit falls somewhere
+    NotFound,
+    // the state limit was reached before the instruction limit
+    GaveUp,
+}
+
+// Breadth-first search over every script built from AND, OR and NOT on the
+// given sensors, shortest first, for one that gets the droid across every
+// hull. Scripts that leave T and J with the same truth tables behave the
+// same however they got there, so only the first one found of each is
+// extended.
+pub fn search(mode: Mode, sensors: &[usize], hulls: &[Hull], limit: usize, states: usize) -> Search {
+
+    if sensors.len() > MAX_SEARCH_SENSORS {
+        panic!("can search over at most {} sensors", MAX_SEARCH_SENSORS);
+    }
+
+    let combinations = 1 << sensors.len();
+    let all: Table = if combinations == 128 { Table::MAX } else { (1 << combinations) - 1 };
+
+    let tables = sensors.iter().enumerate()
+        .map(|(bit, _)| (0..combinations)
+            .filter(|c| c & (1 << bit) != 0)
+            .fold(0 as Table, |table, c| table | (1 << c)))
+        .collect::<Vec<_>>();
+
+    let mut sources = sensors.iter().map(|s| Register::Sensor(*s)).collect::<Vec<_>>();
+    sources.push(Register::T);
+    sources.push(Register::J);
+
+    let survives = |j: Table| {
+        hulls.iter().all(|hull| {
+            hull.traverse(|readings| {
+                let combination = sensors.iter().enumerate()
+                    .filter(|(_, s)| readings[**s])
+                    .fold(0, |c, (bit, _)| c | (1 << bit));
+                return j & (1 << combination) != 0;
+            }).is_none()
+        })
+    };
+
+    let mut parents: Parents = HashMap::new();
+    let mut tested: HashSet<Table> = HashSet::new();
+    let mut frontier = vec![(0, 0)];
+
+    let rebuild = |parents: &Parents, mut state| {
+        let mut instructions = vec![];
+        while let Some((previous, instruction)) = parents.get(&state) {
+            instructions.push(*instruction);
+            state = *previous;
+        }
+        instructions.reverse();
+        return Script { instructions, mode };
+    };
+
+    if survives(0) {
+        return Search::Found(Script { instructions: vec![], mode });
+    }
+    tested.insert(0);
+
+    for _ in 0..limit.min(MAX_INSTRUCTIONS) {
+
+        let mut next = vec![];
+
+        for (t, j) in frontier {
+            for operation in [Operation::And, Operation::Or, Operation::Not].iter() {
+                for source in sources.iter() {
+                    for target in [Register::T, Register::J].iter() {
+
+                        let value = match source {
+                            Register::Sensor(_) => {
+                                tables[sources.iter().position(|s| s == source).unwrap()]
+                            },
+                            Register::T => t,
+                            Register::J => j,
+                        };
+
+                        let old = if *target == Register::T { t } else { j };
+
+                        let new = match operation {
+                            Operation::And => old & value,
+                            Operation::Or => old | value,
+                            Operation::Not => !value & all,
+                        };
+
+                        let state = if *target == Register::T { (new, j) } else { (t, new) };
+
+                        if state == (0, 0) || parents.contains_key(&state) {
+                            continue;
+                        }
+
+                        let instruction = Instruction { operation: *operation, source: *source, target: *target };
+                        parents.insert(state, ((t, j), instruction));
+
+                        if tested.insert(state.1) && survives(state.1) {
+                            return Search::Found(rebuild(&parents, state));
+                        }
+
+                        if parents.len() >= states {
+                            return Search::GaveUp;
+                        }
+
+                        next.push(state);
+                    }
+                }
+            }
+        }
+
+        frontier = next;
+    }
+
+    return Search::NotFound;
+}
@@ -0,0 +1,276 @@
+use std::error::Error;
+use std::fmt;
+
+// The springdroid's memory holds at most this many instructions, not counting
+// the final WALK or RUN.
+pub const MAX_INSTRUCTIONS: usize = 15;
+
+const SENSORS: &str = "ABCDEFGHI";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode { Walk, Run }
+
+impl Mode {
+
+    pub fn from(name: &str) -> Option<Mode> {
+        return match name {
+            "WALK" => Some(Mode::Walk),
+            "RUN" => Some(Mode::Run),
+            _ => None,
+        };
+    }
+
+    pub fn command(&self) -> &'static str {
+        return match self {
+            Mode::Walk => "WALK",
+            Mode::Run => "RUN",
+        };
+    }
+
+    // Walking only extends the sensors four tiles ahead; running extends
+    // them to nine.
+    pub fn sensors(&self) -> usize {
+        return match self {
+            Mode::Walk => 4,
+            Mode::Run => 9,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Register {
+    // ground sensor, 0 for A (one tile ahead) through 8 for I
+    Sensor(usize),
+    T,
+    J,
+}
+
+impl Register {
+
+    pub fn from(name: &str) -> Option<Register> {
+        return match name {
+            "T" => Some(Register::T),
+            "J" => Some(Register::J),
+            _ if name.len() == 1 => SENSORS.find(name).map(Register::Sensor),
+            _ => None,
+        };
+    }
+
+    fn name(&self) -> char {
+        return match self {
+            Register::Sensor(i) => SENSORS.as_bytes()[*i] as char,
+            Register::T => 'T',
+            Register::J => 'J',
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation { And, Or, Not }
+
+impl Operation {
+
+    fn from(name: &str) -> Option<Operation> {
+        return match name {
+            "AND" => Some(Operation::And),
+            "OR" => Some(Operation::Or),
+            "NOT" => Some(Operation::Not),
+            _ => None,
+        };
+    }
+
+    fn name(&self) -> &'static str {
+        return match self {
+            Operation::And => "AND",
+            Operation::Or => "OR",
+            Operation::Not => "NOT",
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    pub operation: Operation,
+    pub source: Register,
+    pub target: Register,
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{} {} {}", self.operation.name(), self.source.name(), self.target.name());
+    }
+}
+
+#[derive(Debug)]
+pub struct ScriptError {
+    line: usize,
+    reason: String,
+}
+
+impl ScriptError {
+    fn new(line: usize, reason: String) -> ScriptError {
+        return ScriptError { line, reason };
+    }
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "line {}: {}", self.line, self.reason);
+    }
+}
+
+impl Error for ScriptError {}
+
+#[derive(Debug, Clone)]
+pub struct Script {
+    pub instructions: Vec<Instruction>,
+    pub mode: Mode,
+}
+
+impl Script {
+
+    // One instruction per line, ending with WALK or RUN. Blank lines and
+    // anything after a `#` are ignored. Every problem in the file is
+    // reported, not just the first.
+    pub fn parse(text: &str) -> Result<Script, Vec<ScriptError>> {
+
+        let mut errors = vec![];
+        let mut instructions = vec![];
+        // line each instruction came from, for errors found after parsing
+        let mut lines = vec![];
+        let mut mode = None;
+
+        for (n, line) in text.lines().enumerate() {
+
+            let n = n + 1;
+            let line = line.split('#').next().unwrap().trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            if mode.is_some() {
+                errors.push(ScriptError::new(n, format!("`{}` after the final WALK or RUN", line)));
+                continue;
+            }
+
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+
+            if let [name] = tokens[..] {
+                match Mode::from(name) {
+                    Some(m) => mode = Some((n, m)),
+                    None => errors.push(ScriptError::new(n, format!("unknown command `{}`", name))),
+                }
+                continue;
+            }
+
+            let (operation, source, target) = match tokens[..] {
+                [o, s, t] => (o, s, t),
+                _ => {
+                    errors.push(ScriptError::new(n, format!("expected `OP X Y`, got `{}`", line)));
+                    continue;
+                },
+            };
+
+            let operation = match Operation::from(operation) {
+                Some(o) => o,
+                None => {
+                    errors.push(ScriptError::new(n, format!("unknown instruction `{}`", operation)));
+                    continue;
+                },
+            };
+
+            let source = match Register::from(source) {
+                Some(r) => r,
+                None => {
+                    errors.push(ScriptError::new(n, format!("unknown register `{}`", source)));
+                    continue;
+                },
+            };
+
+            let target = match Register::from(target) {
+                Some(r @ Register::T) | Some(r @ Register::J) => r,
+                Some(_) => {
+                    errors.push(ScriptError::new(n, format!("cannot write to sensor `{}`", target)));
+                    continue;
+                },
+                None => {
+                    errors.push(ScriptError::new(n, format!("unknown register `{}`", target)));
+                    continue;
+                },
+            };
+
+            instructions.push(Instruction { operation, source, target });
+            lines.push(n);
+        }
+
+        let (n, mode) = match mode {
+            Some(mode) => mode,
+            None => {
+                errors.push(ScriptError::new(text.lines().count(), String::from("missing final WALK or RUN")));
+                return Err(errors);
+            },
+        };
+
+        if instructions.len() > MAX_INSTRUCTIONS {
+            errors.push(ScriptError::new(n, format!(
+                "{} instructions, the springdroid only has room for {}",
+                instructions.len(), MAX_INSTRUCTIONS)));
+        }
+
+        for (instruction, n) in instructions.iter().zip(lines.iter()) {
+            if let Register::Sensor(i) = instruction.source {
+                if i >= mode.sensors() {
+                    errors.push(ScriptError::new(*n, format!(
+                        "sensor `{}` is only available in RUN mode", instruction.source.name())));
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            errors.sort_by_key(|e| e.line);
+            return Err(errors);
+        }
+
+        return Ok(Script { instructions, mode });
+    }
+
+    // Decides whether to jump, given what each sensor sees; `true` is ground.
+    // T and J both start out false, as they do on the springdroid.
+    pub fn jumps(&self, sensors: &[bool]) -> bool {
+
+        let mut t = false;
+        let mut j = false;
+
+        for instruction in self.instructions.iter() {
+
+            let source = match instruction.source {
+                Register::Sensor(i) => sensors[i],
+                Register::T => t,
+                Register::J => j,
+            };
+
+            let target = match instruction.target {
+                Register::T => &mut t,
+                _ => &mut j,
+            };
+
+            *target = match instruction.operation {
+                Operation::And => *target && source,
+                Operation::Or => *target || source,
+                Operation::Not => !source,
+            };
+        }
+
+        return j;
+    }
+}
+
+impl fmt::Display for Script {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for instruction in self.instructions.iter() {
+            writeln!(f, "{}", instruction)?;
+        }
+        return write!(f, "{}", self.mode.command());
+    }
+}
@@ -0,0 +1,177 @@
+mod instruction;
+mod intcode;
+
+use std::collections::VecDeque;
+use std::env;
+use std::io;
+use std::io::BufRead;
+
+use intcode::IntcodeProcessor;
+use intcode::State;
+
+const NAT: isize = 255;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Packet {
+    from: isize,
+    to: isize,
+    x: isize,
+    y: isize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Event {
+    // the first packet addressed to the NAT
+    NatReceived(Packet),
+    // the NAT woke address 0 with the same Y value twice in a row
+    NatRepeated(Packet),
+}
+
+struct Network {
+    machines: Vec<IntcodeProcessor>,
+    queues: Vec<VecDeque<(isize, isize)>>,
+    nat: Option<Packet>,
+    last_wake: Option<Packet>,
+    events: Vec<Event>,
+    log: Vec<(usize, Packet)>,
+    round: usize,
+    verbose: bool,
+}
+
+impl Network {
+
+    fn new(program: &[isize], size: usize, verbose: bool) -> Network {
+
+        let machines = (0..size)
+            .map(|address| {
+                let mut machine = IntcodeProcessor::new(program);
+                machine.push_input(address as isize);
+                machine
+            })
+            .collect();
+
+        return Network {
+            machines,
+            queues: vec![VecDeque::new(); size],
+            nat: None,
+            last_wake: None,
+            events: Vec::new(),
+            log: Vec::new(),
+            round: 0,
+            verbose,
+        };
+    }
+
+    fn send(&mut self, packet: Packet) {
+
+        if self.verbose {
+            eprintln!("[{:>6}] {:>3} -> {:>3}: x={} y={}", self.round, packet.from, packet.to, packet.x, packet.y);
+        }
+        self.log.push((self.round, packet));
+
+        match packet.to {
+            NAT => {
+                if self.nat.is_none() {
+                    self.events.push(Event::NatReceived(packet));
+                }
+                self.nat = Some(packet);
+            },
+            to if to >= 0 && (to as usize) < self.queues.len() => {
+                self.queues[to as usize].push_back((packet.x, packet.y));
+            },
+            to => panic!("packet from {} to unknown address {}", packet.from, to),
+        }
+    }
+
+    // Runs every machine once, in address order, until it blocks on input.
+    // A machine with nothing queued is given -1. Returns whether any machine
+    // sent a packet or had one waiting, i.e. whether the network was busy.
+    fn round(&mut self) -> bool {
+
+        let mut busy = false;
+
+        for address in 0..self.machines.len() {
+
+            match self.queues[address].pop_front() {
+                Some((x, y)) => {
+                    busy = true;
+                    self.machines[address].push_input(x);
+                    self.machines[address].push_input(y);
+                },
+                None => self.machines[address].push_input(-1),
+            }
+
+            if self.machines[address].run() == State::Halted {
+                panic!("machine {} halted", address);
+            }
+
+            let mut outputs = vec![];
+            while let Some(value) = self.machines[address].pop_output() {
+                outputs.push(value);
+            }
+
+            if outputs.len() % 3 != 0 {
+                panic!("machine {} blocked mid-packet ({} values)", address, outputs.len());
+            }
+
+            for chunk in outputs.chunks(3) {
+                busy = true;
+                self.send(Packet { from: address as isize, to: chunk[0], x: chunk[1], y: chunk[2] });
+            }
+        }
+
+        self.round += 1;
+
+        return busy;
+    }
+
+    fn run(&mut self) -> Vec<Event> {
+
+        loop {
+            let busy = self.round();
+
+            if busy || self.queues.iter().any(|q| !q.is_empty()) {
+                continue;
+            }
+
+            let packet = match self.nat {
+                Some(p) => Packet { from: NAT, to: 0, x: p.x, y: p.y },
+                None => panic!("network is idle but the NAT has nothing to send"),
+            };
+
+            if let Some(last) = self.last_wake {
+                if last.y == packet.y {
+                    self.events.push(Event::NatRepeated(packet));
+                    return self.events.clone();
+                }
+            }
+
+            self.last_wake = Some(packet);
+            self.send(packet);
+        }
+    }
+}
+
+fn main() {
+
+    let args = env::args().collect::<Vec<_>>();
+
+    let verbose = args.iter().any(|a| a == "-v");
+    let size = args[1..].iter()
+        .find(|a| *a != "-v")
+        .map(|a| a.parse::<usize>().unwrap())
+        .unwrap_or(50);
+
+    let program = intcode::read_program(&io::stdin().lock().lines().next().unwrap().unwrap());
+
+    let mut network = Network::new(&program, size, verbose);
+
+    for event in network.run() {
+        match event {
+            Event::NatReceived(packet) => println!("part 1: {}", packet.y),
+            Event::NatRepeated(packet) => println!("part 2: {}", packet.y),
+        }
+    }
+
+    println!("{} packets in {} rounds", network.log.len(), network.round);
+}
@@ -0,0 +1,123 @@
+use crate::ascii;
+use crate::intcode::IntcodeProcessor;
+use crate::intcode::State;
+
+// A single command never takes anywhere near this many instructions; a game
+// still running after it has been sent into an endless loop.
+pub const STEP_LIMIT: usize = 1_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    // showing the "Command?" prompt
+    Waiting,
+    Halted,
+    // still running after STEP_LIMIT instructions
+    Stuck,
+}
+
+// The droid's machine together with everything it has done so far, so that
+// cloning a game is a snapshot that can be restored later.
+#[derive(Clone)]
+pub struct Game {
+    computer: IntcodeProcessor,
+}
+
+impl Game {
+
+    pub fn new(program: &[isize]) -> Game {
+        return Game { computer: IntcodeProcessor::new(program) };
+    }
+
+    // Runs up to the first prompt.
+    pub fn start(&mut self) -> (String, Status) {
+        return self.advance();
+    }
+
+    pub fn send(&mut self, command: &str) -> (String, Status) {
+        ascii::send_line(&mut self.computer, command);
+        return self.advance();
+    }
+
+    fn advance(&mut self) -> (String, Status) {
+
+        let mut steps = 0;
+        let mut state = State::Running;
+
+        while state == State::Running && steps < STEP_LIMIT {
+            state = self.computer.step();
+            steps += 1;
+        }
+
+        let (text, _) = ascii::receive(&mut self.computer);
+
+        let status = match state {
+            State::AwaitingInput => Status::Waiting,
+            State::Halted => Status::Halted,
+            State::Running => Status::Stuck,
+        };
+
+        return (text, status);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Room {
+    pub name: String,
+    pub description: String,
+    pub doors: Vec<String>,
+    pub items: Vec<String>,
+}
+
+// Every room description in the game's output, in order. Moving through the
+// pressure-sensitive floor with the wrong weight prints the floor and then
+// the room the droid is thrown back into, so the last room is always the
+// one the droid is standing in.
+pub fn parse_rooms(text: &str) -> Vec<Room> {
+
+    let mut rooms: Vec<Room> = vec![];
+    let mut list: Option<&str> = None;
+
+    for line in text.lines().map(|l| l.trim()) {
+
+        if line.starts_with("== ") && line.ends_with(" ==") {
+            rooms.push(Room {
+                name: line.trim_matches(|c| c == '=' || c == ' ').to_string(),
+                description: String::new(),
+                doors: vec![],
+                items: vec![],
+            });
+            list = None;
+            continue;
+        }
+
+        let room = match rooms.last_mut() {
+            Some(room) => room,
+            None => continue,
+        };
+
+        match line {
+            "" => list = None,
+            "Doors here lead:" => list = Some("doors"),
+            "Items here:" => list = Some("items"),
+            _ if line.starts_with("- ") => match list {
+                Some("doors") => room.doors.push(line[2..].to_string()),
+                Some("items") => room.items.push(line[2..].to_string()),
+                _ => {},
+            },
+            _ if room.description.is_empty() => room.description = line.to_string(),
+            _ => {},
+        }
+    }
+
+    return rooms;
+}
+
+pub fn reverse(direction: &str) -> &'static str {
+    return match direction {
+        "north" => "south",
+        "south" => "north",
+        "east" => "west",
+        "west" => "east",
+        x => panic!("invalid direction {}", x),
+    };
+}
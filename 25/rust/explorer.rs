@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+use crate::adventure;
+use crate::adventure::Game;
+use crate::adventure::Room;
+use crate::adventure::Status;
+
+pub struct Report {
+    pub rooms: usize,
+    pub taken: Vec<String>,
+    pub dangerous: Vec<String>,
+    pub attempts: usize,
+    // everything the game printed once the droid got through
+    pub message: String,
+}
+
+struct Map {
+    rooms: HashMap<String, Room>,
+    // room -> direction -> room
+    doors: HashMap<String, HashMap<String, String>>,
+    // the room the droid gets thrown back into, and the door from it onto
+    // the pressure-sensitive floor
+    checkpoint: Option<(String, String)>,
+    floor: Option<String>,
+    // rooms in the order they were discovered
+    order: Vec<String>,
+}
+
+impl Map {
+
+    fn connect(&mut self, from: &str, direction: &str, to: &str) {
+        self.doors.entry(from.to_string()).or_default()
+            .insert(direction.to_string(), to.to_string());
+        self.doors.entry(to.to_string()).or_default()
+            .insert(adventure::reverse(direction).to_string(), from.to_string());
+    }
+
+    // Shortest list of moves between two rooms, never crossing the floor.
+    fn route(&self, from: &str, to: &str) -> Vec<String> {
+
+        let mut previous: HashMap<&str, (&str, &str)> = HashMap::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(from);
+
+        while let Some(at) = queue.pop_front() {
+
+            if at == to {
+                let mut moves = vec![];
+                let mut at = to;
+                while at != from {
+                    let (before, direction) = previous[at];
+                    moves.push(direction.to_string());
+                    at = before;
+                }
+                moves.reverse();
+                return moves;
+            }
+
+            for (direction, next) in self.doors.get(at).into_iter().flatten() {
+                if Some(next) == self.floor.as_ref() || next == from || previous.contains_key(next.as_str()) {
+                    continue;
+                }
+                previous.insert(next.as_str(), (at, direction.as_str()));
+                queue.push_back(next);
+            }
+        }
+
+        panic!("no route from {} to {}", from, to);
+    }
+}
+
+fn log(verbose: bool, message: String) {
+    if verbose {
+        eprintln!("{}", message);
+    }
+}
+
+fn expect_waiting(status: Status, command: &str) {
+    if status != Status::Waiting {
+        panic!("game ended unexpectedly after `{}` ({:?})", command, status);
+    }
+}
+
+// Visits every room reachable without getting past the floor, restoring a
+// snapshot of the room being explored before trying each of its doors.
+fn survey(program: &[isize], verbose: bool) -> (Map, String) {
+
+    let mut game = Game::new(program);
+    let (text, _) = game.start();
+    let start = adventure::parse_rooms(&text).pop().expect("game did not describe the first room");
+
+    let mut map = Map {
+        rooms: HashMap::new(),
+        doors: HashMap::new(),
+        checkpoint: None,
+        floor: None,
+        order: vec![start.name.clone()],
+    };
+
+    let mut queue = VecDeque::new();
+    queue.push_back((game, start.clone()));
+    map.rooms.insert(start.name.clone(), start.clone());
+
+    while let Some((game, room)) = queue.pop_front() {
+
+        log(verbose, format!("{}: doors {}, items {}", room.name, room.doors.join(", "),
+            if room.items.is_empty() { String::from("none") } else { room.items.join(", ") }));
+
+        for direction in room.doors.iter() {
+
+            if map.doors.get(&room.name).map(|d| d.contains_key(direction)).unwrap_or(false) {
+                continue;
+            }
+
+            let mut next = game.clone();
+            let (text, status) = next.send(direction);
+            expect_waiting(status, direction);
+
+            let mut rooms = adventure::parse_rooms(&text);
+            let entered = rooms.remove(0);
+            map.connect(&room.name, direction, &entered.name);
+
+            // thrown straight back out: this is the pressure-sensitive floor
+            if !rooms.is_empty() {
+                log(verbose, format!("{}: {} leads onto {}", room.name, direction, entered.name));
+                map.checkpoint = Some((room.name.clone(), direction.clone()));
+                map.floor = Some(entered.name.clone());
+                map.rooms.insert(entered.name.clone(), entered);
+                continue;
+            }
+
+            if !map.rooms.contains_key(&entered.name) {
+                map.order.push(entered.name.clone());
+                map.rooms.insert(entered.name.clone(), entered.clone());
+                queue.push_back((next, entered));
+            }
+        }
+    }
+
+    return (map, start.name);
+}
+
+// An item is safe if taking it leaves the game waiting for the next command
+// and the droid can still move afterwards.
+fn safe(game: &Game, item: &str, door: &str) -> bool {
+
+    let mut probe = game.clone();
+
+    let (_, status) = probe.send(&format!("take {}", item));
+    if status != Status::Waiting {
+        return false;
+    }
+
+    let (text, status) = probe.send(door);
+    return status == Status::Waiting && !text.contains("can't move");
+}
+
+pub fn solve(program: &[isize], verbose: bool) -> Report {
+
+    let (map, start) = survey(program, verbose);
+
+    let (checkpoint, door) = map.checkpoint.clone().expect("pressure-sensitive floor not found");
+
+    let mut game = Game::new(program);
+    game.start();
+    let mut at = start;
+
+    let mut taken = vec![];
+    let mut dangerous = vec![];
+
+    for name in map.order.iter() {
+
+        let room = &map.rooms[name];
+        if room.items.is_empty() {
+            continue;
+        }
+
+        for direction in map.route(&at, name) {
+            let (_, status) = game.send(&direction);
+            expect_waiting(status, &direction);
+        }
+        at = name.clone();
+
+        for item in room.items.iter() {
+            if safe(&game, item, &room.doors[0]) {
+                let command = format!("take {}", item);
+                let (_, status) = game.send(&command);
+                expect_waiting(status, &command);
+                log(verbose, format!("took {} in {}", item, name));
+                taken.push(item.clone());
+            } else {
+                log(verbose, format!("left {} in {}", item, name));
+                dangerous.push(item.clone());
+            }
+        }
+    }
+
+    for direction in map.route(&at, &checkpoint) {
+        let (_, status) = game.send(&direction);
+        expect_waiting(status, &direction);
+    }
+
+    // Step through every subset of the items in Gray code order, so that
+    // each attempt only takes or drops a single item.
+    let mut held = vec![true; taken.len()];
+
+    for i in 0..(1usize << taken.len()) {
+
+        let gray = i ^ (i >> 1);
+
+        for (bit, item) in taken.iter().enumerate() {
+            let wanted = gray & (1 << bit) == 0;
+            if held[bit] != wanted {
+                let command = format!("{} {}", if wanted { "take" } else { "drop" }, item);
+                let (_, status) = game.send(&command);
+                expect_waiting(status, &command);
+                held[bit] = wanted;
+            }
+        }
+
+        let (text, status) = game.send(&door);
+
+        if status == Status::Halted {
+            log(verbose, format!("got through carrying {}", taken.iter().zip(held.iter())
+                .filter(|(_, h)| **h)
+                .map(|(t, _)| t.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")));
+            return Report { rooms: map.rooms.len(), taken, dangerous, attempts: i + 1, message: text };
+        }
+
+        expect_waiting(status, &door);
+    }
+
+    panic!("no combination of {} items gets past the floor", taken.len());
+}
+
+// The airlock password is the only number in the final message.
+pub fn password(message: &str) -> Option<String> {
+    return message.split(|c: char| !c.is_ascii_digit())
+        .find(|w| !w.is_empty())
+        .map(|w| w.to_string());
+}
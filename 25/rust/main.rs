@@ -0,0 +1,36 @@
+mod adventure;
+mod ascii;
+mod explorer;
+mod instruction;
+mod intcode;
+mod shell;
+
+use std::env;
+use std::io;
+use std::io::BufRead;
+
+fn main() {
+
+    let args = env::args().collect::<Vec<_>>();
+
+    let program = intcode::read_program(&io::stdin().lock().lines().next().unwrap().unwrap());
+
+    // commands for the interactive shell follow the program on stdin
+    if args.get(1).map(|s| s.as_str()) == Some("play") {
+        shell::run(&program);
+        return;
+    }
+
+    let verbose = args.iter().any(|a| a == "-v");
+
+    let report = explorer::solve(&program, verbose);
+
+    if verbose {
+        print!("{}", report.message);
+    }
+
+    println!("{} rooms, took {}, left {}, {} attempts at the floor",
+        report.rooms, report.taken.join(", "), report.dangerous.join(", "), report.attempts);
+
+    println!("part 1: {}", explorer::password(&report.message).expect("no password in the final message"));
+}
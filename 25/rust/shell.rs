@@ -0,0 +1,147 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+
+use crate::adventure;
+use crate::adventure::Game;
+use crate::adventure::Status;
+
+const HELP: &str = "\
+Anything not starting with `:` or `!` is sent to the droid as a command.
+  n s e w             north, south, east, west
+  i                   inv
+commands:
+  :save [name]        snapshot the game (default name: quick)
+  :load [name]        restore a snapshot
+  :saves              list snapshots
+  :history            list the commands sent so far
+  !!                  repeat the last command
+  !n                  repeat command n from :history
+  :help               show this text
+  :quit               leave the game";
+
+fn expand(command: &str) -> &str {
+    return match command {
+        "n" => "north",
+        "s" => "south",
+        "e" => "east",
+        "w" => "west",
+        "i" => "inv",
+        x => x,
+    };
+}
+
+fn show(text: &str, status: Status) {
+
+    print!("{}", text);
+
+    match status {
+        Status::Waiting => {},
+        Status::Halted => println!("[game over, :load a snapshot or :quit]"),
+        Status::Stuck => println!("[no response after {} instructions, :load a snapshot or :quit]",
+            adventure::STEP_LIMIT),
+    }
+}
+
+// Expands `!!` and `!n` into the command they refer to.
+fn recall(history: &[String], line: &str) -> Result<String, String> {
+
+    let index = match &line[1..] {
+        "!" => history.len(),
+        n => n.parse::<usize>().map_err(|_| format!("bad history reference: {}", line))?,
+    };
+
+    return match index.checked_sub(1).and_then(|i| history.get(i)) {
+        Some(command) => Ok(command.clone()),
+        None => Err(format!("no command {} in history", index)),
+    };
+}
+
+pub fn run(program: &[isize]) {
+
+    let mut game = Game::new(program);
+    let mut saves: BTreeMap<String, (Game, Status)> = BTreeMap::new();
+    let mut history: Vec<String> = vec![];
+
+    println!("Cryostasis shell, :help for commands");
+
+    let (text, mut status) = game.start();
+    show(&text, status);
+
+    loop {
+        print!("> ");
+        io::stdout().flush().unwrap();
+
+        let line = match io::stdin().lock().lines().next() {
+            Some(line) => line.unwrap(),
+            None => break,
+        };
+        let line = line.trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        let args = line.split_whitespace().collect::<Vec<_>>();
+        let name = args.get(1).cloned().unwrap_or("quick").to_string();
+
+        let command = match args[0] {
+            ":quit" | ":q" => break,
+            ":help" | ":h" => {
+                println!("{}", HELP);
+                continue;
+            },
+            ":save" => {
+                saves.insert(name.clone(), (game.clone(), status));
+                println!("saved {}", name);
+                continue;
+            },
+            ":load" => {
+                match saves.get(&name) {
+                    Some((saved, saved_status)) => {
+                        game = saved.clone();
+                        status = *saved_status;
+                        println!("loaded {}", name);
+                    },
+                    None => println!("error: no snapshot called {}", name),
+                }
+                continue;
+            },
+            ":saves" => {
+                saves.keys().for_each(|name| println!("  {}", name));
+                continue;
+            },
+            ":history" => {
+                history.iter().enumerate().for_each(|(i, c)| println!("{:>4}  {}", i + 1, c));
+                continue;
+            },
+            x if x.starts_with(':') => {
+                println!("error: unknown command {} (try :help)", x);
+                continue;
+            },
+            x if x.starts_with('!') => match recall(&history, x) {
+                Ok(command) => {
+                    println!("{}", command);
+                    command
+                },
+                Err(err) => {
+                    println!("error: {}", err);
+                    continue;
+                },
+            },
+            _ => expand(line).to_string(),
+        };
+
+        if status != Status::Waiting {
+            println!("error: the game is over, :load a snapshot to keep playing");
+            continue;
+        }
+
+        history.push(command.clone());
+
+        let (text, next) = game.send(&command);
+        status = next;
+        show(&text, status);
+    }
+}